@@ -15,7 +15,9 @@
 @fragment
 fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
     let a = textureSample(background_texture, background_sampler, mesh.uv);
-    let b = textureSample(base_color_texture, base_color_sampler, mesh.uv);
+    var b = textureSample(base_color_texture, base_color_sampler, mesh.uv);
+    // Damaged buildings get darker and redder
+    b = vec4(b.xyz * (vec3(1.0) - progress.y * vec3(0.35, 0.7, 0.7)), b.w);
     let c = textureSample(hovered_texture, hovered_sampler, mesh.uv);
     let d = textureSample(selected_texture, selected_sampler, mesh.uv);
 
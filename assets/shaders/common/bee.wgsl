@@ -12,6 +12,7 @@ struct BeeMaterial {
     phase: f32,
     damage_time: f32,
     upgrade_time: f32,
+    shield: f32,
 };
 
 const COLOR_MATERIAL_FLAGS_TEXTURE_BIT: u32 = 1u;
@@ -44,6 +45,8 @@ fn get_blood_color(uvi: vec2<f32>, intensity: f32) -> vec4<f32> {
 
 fn get_color(uv: vec2<f32>, time: f32) -> vec4<f32> {
     var color = get_shape_color(uv);
+    // Tint of elite enemies, white for everyone else
+    color = vec4(color.xyz * material.color.xyz, color.w);
     color = mix_colors(color, get_overlay_color(uv));
     color = mix_colors(color, get_wing_color(uv, material.phase + time));
 
@@ -67,5 +70,12 @@ fn get_color(uv: vec2<f32>, time: f32) -> vec4<f32> {
     upgrade_intensity = max(1.0 - upgrade_intensity * 2.0, 0.0);
     color = color * vec4(vec3(10.0, 10.0, 10.0) * upgrade_intensity + vec3(1.0), 1.0);
 
+    // Shield bubble, fades out as the shield is used up
+    let v = (uv - 0.5) * 2.0;
+    let d = sqrt(v.x * v.x + v.y * v.y);
+    let bubble = step(0.001, material.shield) * smoothstep(0.7, 0.95, d) * step(d, 1.0);
+    let shimmer = 0.85 + 0.15 * sin(time * 4.0 + material.phase);
+    color = mix_colors(color, vec4(0.45, 0.75, 1.0, bubble * (0.25 + 0.45 * material.shield) * shimmer));
+
     return color;
 }
@@ -0,0 +1,177 @@
+//! Compares the spatial grid against the plain scans it replaced, on a late
+//! game sized crowd of 5,000 units:
+//!
+//! `cargo bench --bench spatial_grid`
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy_jam_4::core::{
+    collision_system, spatial_grid_system, universal_behaviour_system, BeeType, EnemyType, Faction,
+    GameRng, HiveAlarm, HiveBuildings, HiveDefinition, HiveMap, NavigationResult, NavigationTarget,
+    SpatialEntry, SpatialGrid, UnitRegistry,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const UNITS: usize = 5000;
+const TICKS: u32 = 30;
+const ARENA: f32 = 1200.0;
+
+fn random_entries(rng: &mut StdRng) -> Vec<SpatialEntry> {
+    (0..UNITS)
+        .map(|i| SpatialEntry {
+            entity: Entity::from_raw(i as u32),
+            position: Vec2::new(rng.gen_range(-ARENA..ARENA), rng.gen_range(-ARENA..ARENA)),
+            radius: rng.gen_range(4.0..13.0),
+            is_bee: i % 2 == 0,
+            building: None,
+        })
+        .collect()
+}
+
+fn time<T>(label: &str, f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    println!("{:<32} {:>10.2?}", label, elapsed);
+    (result, elapsed)
+}
+
+fn report_speedup(naive: Duration, grid: Duration) {
+    println!(
+        "{:<32} {:>9.1}x\n",
+        "speedup",
+        naive.as_secs_f64() / grid.as_secs_f64()
+    );
+}
+
+/// Pairs of touching bee and enemy bodies.
+fn bench_contacts(entries: &[SpatialEntry], grid: &SpatialGrid) {
+    let (naive_pairs, naive) = time("contacts, double loop", || {
+        let mut pairs = 0;
+        for a in entries.iter().filter(|e| e.is_bee) {
+            for b in entries.iter().filter(|e| !e.is_bee) {
+                if a.position.distance_squared(b.position) <= (a.radius + b.radius).powi(2) {
+                    pairs += 1;
+                }
+            }
+        }
+        pairs
+    });
+
+    let (grid_pairs, grid_time) = time("contacts, grid", || {
+        let mut pairs = 0;
+        for a in entries.iter().filter(|e| e.is_bee) {
+            grid.for_each_in_radius(a.position, a.radius + grid.get_max_radius(), |b| {
+                if !b.is_bee
+                    && a.position.distance_squared(b.position) <= (a.radius + b.radius).powi(2)
+                {
+                    pairs += 1;
+                }
+            });
+        }
+        pairs
+    });
+
+    assert_eq!(naive_pairs, grid_pairs);
+    report_speedup(naive, grid_time);
+}
+
+/// Nearest opposing unit within a bee's engagement radius, for every unit.
+fn bench_targets(entries: &[SpatialEntry], grid: &SpatialGrid) {
+    let radius = 160.0;
+
+    let (naive_found, naive) = time("nearest target, full scan", || {
+        entries
+            .iter()
+            .filter(|a| {
+                entries
+                    .iter()
+                    .filter(|b| b.is_bee != a.is_bee)
+                    .any(|b| a.position.distance_squared(b.position) < radius * radius)
+            })
+            .count()
+    });
+
+    let (grid_found, grid_time) = time("nearest target, grid", || {
+        entries
+            .iter()
+            .filter(|a| {
+                grid.find_nearest(a.position, radius, |b| b.is_bee != a.is_bee)
+                    .is_some()
+            })
+            .count()
+    });
+
+    assert_eq!(naive_found, grid_found);
+    report_speedup(naive, grid_time);
+}
+
+/// The real systems, grid rebuild included.
+fn bench_systems(rng: &mut StdRng) {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.init_resource::<SpatialGrid>();
+    app.init_resource::<HiveAlarm>();
+    app.init_resource::<HiveBuildings>();
+    app.init_resource::<GameRng>();
+    app.insert_resource(HiveMap::open(&HiveDefinition::default()));
+    app.add_systems(
+        Update,
+        (spatial_grid_system, universal_behaviour_system, collision_system).chain(),
+    );
+
+    let units = UnitRegistry::default();
+    for i in 0..UNITS {
+        let position = Vec3::new(rng.gen_range(-ARENA..ARENA), rng.gen_range(-ARENA..ARENA), 0.0);
+        let mut entity = app.world.spawn((
+            TransformBundle::from_transform(Transform::from_translation(position)),
+            NavigationTarget::None,
+            NavigationResult::default(),
+        ));
+        if i % 2 == 0 {
+            let bee = BeeType::Defender(0);
+            entity.insert((
+                bee,
+                units.get(bee).get_creature(),
+                units.get(bee).get_rigid_body(),
+                units.get(bee).get_behaviour(),
+                Faction::Bees,
+            ));
+        } else {
+            let enemy = EnemyType::Wasp(0);
+            entity.insert((
+                enemy,
+                units.get(enemy).get_creature(),
+                units.get(enemy).get_rigid_body(),
+                units.get(enemy).get_behaviour(),
+                Faction::Enemies,
+            ));
+        }
+    }
+
+    app.update();
+    time(&format!("behaviour + collision, {} ticks", TICKS), || {
+        for _ in 0..TICKS {
+            app.update();
+        }
+    });
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let entries = random_entries(&mut rng);
+
+    let mut grid = SpatialGrid::default();
+    time("grid rebuild", || {
+        grid.clear();
+        for entry in entries.iter() {
+            grid.insert(*entry);
+        }
+    });
+    println!();
+
+    bench_contacts(&entries, &grid);
+    bench_targets(&entries, &grid);
+    bench_systems(&mut rng);
+}
@@ -0,0 +1,278 @@
+//! Runs the simulation without a window, renderer or audio and prints a JSON
+//! report. Used for balance testing:
+//!
+//! `cargo run --release --bin simulate -- --seed 42 --waves 10 --build-order build_orders/basic.json`
+//!
+//! With `--replay FILE` it plays a recorded run instead and reports where the
+//! replayed state first diverged from the recording, if it did.
+
+use std::{collections::BTreeMap, env, fs, path::Path, process};
+
+use bevy::{prelude::*, utils::HashSet};
+use bevy_jam_4::{
+    core::{
+        BeeType, BuildingCommand, BuildingCommandRejected, BuildingKind,
+        CurrencyStorage, CurrencyValues, EnemyType, GameInfo, LivingCreature, SimulationSet,
+        SIMULATION_HZ,
+    },
+    headless::{headless_app, start_headless_run},
+    levels::Scenario,
+    replay::{Replay, ReplayPlayback, ReplayPlugin},
+};
+use serde::{Deserialize, Serialize};
+
+const CURRENCY_SAMPLE_INTERVAL: f32 = 10.0;
+
+struct Args {
+    seed: u64,
+    waves: usize,
+    scenario: usize,
+    build_order: Vec<BuildStep>,
+    max_time: f32,
+    replay: Option<Replay>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+enum BuildAction {
+    Build { index: usize, kind: BuildingKind },
+    Upgrade { index: usize },
+    Destroy { index: usize },
+    Order { index: usize, count: u32 },
+}
+
+/// One step of a scripted build order, e.g.
+/// `{ "at": 30.0, "build": { "index": 6, "kind": "Armory" } }`.
+#[derive(Deserialize, Clone, Copy, Debug)]
+struct BuildStep {
+    /// Simulation time in seconds.
+    at: f32,
+    #[serde(flatten)]
+    action: BuildAction,
+}
+
+#[derive(Serialize)]
+struct CurrencySample {
+    time: f32,
+    stored: CurrencyValues,
+}
+
+#[derive(Serialize)]
+struct Report {
+    seed: u64,
+    scenario: usize,
+    waves_requested: usize,
+    waves_survived: usize,
+    game_over: bool,
+    victory: bool,
+    simulated_seconds: f32,
+    bees_lost: BTreeMap<String, usize>,
+    enemies_killed: BTreeMap<String, usize>,
+    rejected_commands: Vec<String>,
+    currency_curve: Vec<CurrencySample>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replay_diverged_at: Option<u64>,
+}
+
+#[derive(Resource, Default)]
+struct DeathCounter {
+    counted: HashSet<Entity>,
+    bees_lost: BTreeMap<String, usize>,
+    enemies_killed: BTreeMap<String, usize>,
+}
+
+fn count_deaths_system(
+    creatures: Query<(Entity, &LivingCreature, Option<&BeeType>, Option<&EnemyType>)>,
+    mut counter: ResMut<DeathCounter>,
+) {
+    for (e, creature, bee, enemy) in creatures.iter() {
+        if !creature.is_dead() || !counter.counted.insert(e) {
+            continue;
+        }
+        if let Some(bee) = bee {
+            *counter.bees_lost.entry(format!("{:?}", bee)).or_default() += 1;
+        }
+        if let Some(enemy) = enemy {
+            *counter.enemies_killed.entry(format!("{:?}", enemy)).or_default() += 1;
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct RejectedCommands(Vec<String>);
+
+fn count_rejected_commands_system(
+    mut rejected: EventReader<BuildingCommandRejected>,
+    mut rejected_commands: ResMut<RejectedCommands>,
+    time: Res<Time>,
+) {
+    for rejection in rejected.read() {
+        rejected_commands.0.push(format!(
+            "{:.1}s {:?}: {}",
+            time.elapsed_seconds(),
+            rejection.command,
+            rejection.reason
+        ));
+    }
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!(
+            "Usage: simulate [--seed N] [--waves N] [--scenario N] [--build-order FILE] [--max-time SECONDS] [--replay FILE]"
+        );
+        process::exit(2);
+    });
+
+    let mut app = headless_app(args.seed, args.scenario).unwrap_or_else(|e| {
+        eprintln!("Failed to build hive map: {}", e);
+        process::exit(1);
+    });
+    app.init_resource::<DeathCounter>();
+    app.init_resource::<RejectedCommands>();
+
+    if let Some(replay) = args.replay.clone() {
+        app.add_plugins(ReplayPlugin);
+        app.insert_resource(ReplayPlayback::new(replay));
+    }
+    app.add_systems(FixedUpdate, count_deaths_system.after(SimulationSet::Logic));
+    app.add_systems(Update, count_rejected_commands_system);
+
+    // Build orders are timed from the start of the run
+    if let Err(e) = start_headless_run(&mut app) {
+        eprintln!("Failed to start the run: {}", e);
+        process::exit(1);
+    }
+
+    let mut build_order = args.build_order.clone();
+    build_order.sort_by(|a, b| a.at.total_cmp(&b.at));
+    let mut next_step = 0;
+
+    let mut currency_curve = vec![];
+    let mut next_sample = 0.0;
+    let mut ticks: u64 = 0;
+
+    loop {
+        app.update();
+        ticks += 1;
+        let time = ticks as f32 / SIMULATION_HZ as f32;
+
+        while next_step < build_order.len() && build_order[next_step].at <= time {
+            apply_build_step(&mut app.world, build_order[next_step].action);
+            next_step += 1;
+        }
+
+        if time >= next_sample {
+            currency_curve.push(CurrencySample {
+                time,
+                stored: app.world.resource::<CurrencyStorage>().stored,
+            });
+            next_sample += CURRENCY_SAMPLE_INTERVAL;
+        }
+
+        let done = match app.world.get_resource::<ReplayPlayback>() {
+            Some(playback) => playback.finished,
+            None => {
+                app.world.resource::<GameInfo>().end
+                    || get_waves_cleared(&mut app.world) >= args.waves
+            }
+        };
+        if done || time >= args.max_time {
+            break;
+        }
+    }
+
+    let waves_survived = get_waves_cleared(&mut app.world);
+    let game = app.world.resource::<GameInfo>();
+    let counter = app.world.resource::<DeathCounter>();
+
+    let report = Report {
+        seed: args.seed,
+        scenario: args.scenario,
+        waves_requested: args.waves,
+        waves_survived,
+        game_over: game.end,
+        victory: game.victory,
+        simulated_seconds: ticks as f32 / SIMULATION_HZ as f32,
+        bees_lost: counter.bees_lost.clone(),
+        enemies_killed: counter.enemies_killed.clone(),
+        rejected_commands: app.world.resource::<RejectedCommands>().0.clone(),
+        currency_curve,
+        replay_diverged_at: app
+            .world
+            .get_resource::<ReplayPlayback>()
+            .and_then(|playback| playback.diverged_at),
+    };
+
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
+
+fn get_waves_cleared(world: &mut World) -> usize {
+    world
+        .query::<&Scenario>()
+        .iter(world)
+        .next()
+        .map_or(0, |scenario| scenario.waves_cleared)
+}
+
+fn apply_build_step(world: &mut World, action: BuildAction) {
+    match action {
+        BuildAction::Build { index, kind } => {
+            world.send_event(BuildingCommand::Build { index, kind });
+        }
+        BuildAction::Upgrade { index } => {
+            world.send_event(BuildingCommand::Upgrade { index });
+        }
+        BuildAction::Destroy { index } => {
+            world.send_event(BuildingCommand::Destroy { index });
+        }
+        BuildAction::Order { index, count } => {
+            world.send_event_batch((0..count).map(|_| BuildingCommand::Order { index }));
+        }
+    }
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        seed: 0,
+        waves: 10,
+        scenario: 0,
+        build_order: vec![],
+        max_time: 3600.0,
+        replay: None,
+    };
+
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--seed" => args.seed = value()?.parse().map_err(|_| "Invalid --seed")?,
+            "--waves" => args.waves = value()?.parse().map_err(|_| "Invalid --waves")?,
+            "--scenario" => args.scenario = value()?.parse().map_err(|_| "Invalid --scenario")?,
+            "--max-time" => args.max_time = value()?.parse().map_err(|_| "Invalid --max-time")?,
+            "--build-order" => {
+                let path = value()?;
+                let json = fs::read(&path).map_err(|e| format!("{}: {}", path, e))?;
+                args.build_order =
+                    serde_json::from_slice(&json).map_err(|e| format!("{}: {}", path, e))?;
+            }
+            "--replay" => {
+                let path = value()?;
+                let replay = Replay::read(Path::new(&path)).map_err(|e| format!("{}: {}", path, e))?;
+                args.replay = Some(replay);
+            }
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    if let Some(replay) = &args.replay {
+        if !args.build_order.is_empty() {
+            return Err(String::from("--replay can't be combined with --build-order"));
+        }
+        args.seed = replay.seed;
+        args.scenario = replay.scenario;
+    }
+
+    Ok(args)
+}
@@ -1,7 +1,13 @@
 use bevy::{prelude::*, render::mesh::shape::Quad, sprite::Mesh2dHandle};
 
+use crate::core::{HiveBuildings, HiveDefinition};
+
+/// Image of the hive, `image` is the path it was made from.
 #[derive(Component)]
-pub struct HiveVisual;
+pub struct HiveVisual {
+    pub image: String,
+    pub size: Vec2,
+}
 
 #[derive(Component)]
 pub struct BackgroundVisual;
@@ -20,14 +26,9 @@ pub fn spawn_hive_visual(
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<ColorMaterial>,
     asset_server: &mut AssetServer,
+    hive: &HiveDefinition,
 ) {
-    commands.spawn((
-        materials.add(ColorMaterial::from(asset_server.load("images/Hive.png"))),
-        Mesh2dHandle(meshes.add(Quad::new(Vec2::new(320.0, 320.0)).into())),
-        TransformBundle::from_transform(Transform::from_xyz(0., 0., -10.)),
-        VisibilityBundle::default(),
-        HiveVisual,
-    ));
+    spawn_hive_image(commands, meshes, materials, asset_server, hive);
 
     commands.spawn((
         materials.add(ColorMaterial::from(asset_server.load("images/Tree.png"))),
@@ -61,3 +62,40 @@ pub fn spawn_hive_visual(
         BackgroundVisual2,
     ));
 }
+
+fn spawn_hive_image(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    asset_server: &AssetServer,
+    hive: &HiveDefinition,
+) {
+    commands.spawn((
+        materials.add(ColorMaterial::from(asset_server.load(&hive.image))),
+        Mesh2dHandle(meshes.add(Quad::new(hive.world_size).into())),
+        TransformBundle::from_transform(Transform::from_xyz(0., 0., -10.)),
+        VisibilityBundle::default(),
+        HiveVisual {
+            image: hive.image.clone(),
+            size: hive.world_size,
+        },
+    ));
+}
+
+/// A loaded save can bring a different hive than the one the run started with.
+pub fn hive_visual_system(
+    mut commands: Commands,
+    hive_buildings: Res<HiveBuildings>,
+    visuals: Query<(Entity, &HiveVisual)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    let hive = &hive_buildings.hive;
+    for (e, visual) in visuals.iter() {
+        if visual.image != hive.image || visual.size != hive.world_size {
+            commands.entity(e).despawn();
+            spawn_hive_image(&mut commands, &mut meshes, &mut materials, &asset_server, hive);
+        }
+    }
+}
@@ -73,7 +73,7 @@ pub fn in_game_camera_system(
     >,
     mouse: Res<Input<MouseButton>>,
     mut target_zoom: Local<Option<f32>>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
 ) {
     if *target_zoom == None {
         *target_zoom = Some(1.0);
@@ -25,6 +25,35 @@ pub enum AppState {
     InGame,
 }
 
+/// Gameplay runs in [`FixedUpdate`] so it doesn't depend on the frame rate.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum SimulationSet {
+    /// Waves, buildings, behaviours and fights.
+    Logic,
+    /// Movement, collisions and integration.
+    Physics,
+}
+
+/// Steps of [`SimulationSet::Logic`], run one after another so every system
+/// sees the same world each tick, regardless of how bevy schedules them.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum LogicSet {
+    /// Scenario progress and spawning of new waves.
+    Waves,
+    /// Building commands from the player or a replay.
+    Commands,
+    /// Deciding what each unit wants to do.
+    Behaviour,
+    /// Paths towards the chosen targets.
+    Navigation,
+    /// Attacks, alarms and healing.
+    Combat,
+    /// Income, shields, deaths and destroyed buildings.
+    Upkeep,
+}
+
+pub const SIMULATION_HZ: f64 = 60.0;
+
 pub const COMMON_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(1312296983110122547);
 pub const UVDXDY_COMMON_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(1212291923110122247);
 pub const BEE_COMMON_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(1412291983110122547);
@@ -57,6 +86,7 @@ impl Plugin for CorePlugin {
         app.add_plugins(NavigationPlugin);
         app.add_plugins(UiPlugin);
         app.add_plugins(ModelPlugin);
+        app.add_plugins(ModelVisualsPlugin);
 
         app.add_systems(Update, font_loader);
         app.add_systems(Startup, play_background_audio);
@@ -1,15 +1,16 @@
 use crate::core::{Faction, NavigationResult, NavigationTarget};
 
 use super::{
-    CurrencyGainPerMinute, LivingCreature, MoveToNavigationTargetBehaviour, RigidBody,
-    SmartOrientation, UniversalBehaviour, UniversalMaterial, BEE_MESH,
+    get_animation_phase, CurrencyGainPerMinute, LivingCreature, MoveToNavigationTargetBehaviour,
+    PreviousTranslation, RigidBody, SmartOrientation, UnitRegistry, UniversalBehaviour,
+    UniversalMaterial, BEE_MESH,
 };
 
 use bevy::{prelude::*, sprite::Mesh2dHandle};
-use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug, Component, EnumIter)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug, Component, EnumIter, Serialize, Deserialize)]
 pub enum BeeType {
     #[default]
     Baby,
@@ -26,13 +27,16 @@ pub fn update_bee_material_system(
     mut commands: Commands,
     mut bees: Query<(Entity, &BeeType, Option<&Handle<UniversalMaterial>>), Changed<BeeType>>,
     mut materials: ResMut<Assets<UniversalMaterial>>,
+    units: Res<UnitRegistry>,
 ) {
     for (e, bee, material) in bees.iter_mut() {
-        let mut new_material: UniversalMaterial = bee.clone().into();
+        let mut new_material = units.get(*bee).get_material();
+        new_material.props.phase = get_animation_phase(e);
         if let Some(material) = material {
             if let Some(material) = materials.get(material) {
                 new_material.props.upgrade_time = material.props.upgrade_time;
                 new_material.props.damage_time = material.props.damage_time;
+                new_material.props.shield = material.props.shield;
             }
         }
         commands.entity(e).insert(materials.add(new_material));
@@ -43,6 +47,7 @@ pub fn update_bee_material_system(
 pub struct BeeBundle {
     pub visiblity: VisibilityBundle,
     pub transform: TransformBundle,
+    pub previous_translation: PreviousTranslation,
     pub mesh: Mesh2dHandle,
     pub bee_type: BeeType,
     pub creature: LivingCreature,
@@ -56,21 +61,20 @@ pub struct BeeBundle {
     pub gain: CurrencyGainPerMinute,
 }
 
-impl From<(BeeType, Vec2)> for BeeBundle {
-    fn from(value: (BeeType, Vec2)) -> Self {
-        let (bee_type, position) = value;
+impl BeeBundle {
+    pub fn new(bee_type: BeeType, position: Vec3, units: &UnitRegistry) -> Self {
+        let stats = units.get(bee_type);
 
         BeeBundle {
             visiblity: VisibilityBundle::default(),
-            transform: TransformBundle::from_transform(Transform::from_translation(
-                position.extend(thread_rng().gen_range(0.0..1.0)),
-            )),
+            transform: TransformBundle::from_transform(Transform::from_translation(position)),
+            previous_translation: PreviousTranslation(position),
             mesh: Mesh2dHandle(BEE_MESH),
             bee_type,
-            creature: LivingCreature::from(bee_type),
-            rigid_body: RigidBody::from(bee_type),
-            behaviour: UniversalBehaviour::from(bee_type),
-            gain: CurrencyGainPerMinute::from(bee_type),
+            creature: stats.get_creature(),
+            rigid_body: stats.get_rigid_body(),
+            behaviour: stats.get_behaviour(),
+            gain: stats.get_gain(),
             target: NavigationTarget::None,
             result: NavigationResult::default(),
             move_behaviour: MoveToNavigationTargetBehaviour,
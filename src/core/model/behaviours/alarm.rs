@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{get_spawn_ordered, Faction, LivingCreature, SpawnOrder},
+    utils::FlatProvider,
+};
+
+/// Radius an alarm covers right after it was raised.
+pub const ALARM_RADIUS: f32 = 150.0;
+/// Seconds until an alarm has shrunk to nothing.
+pub const ALARM_DURATION: f32 = 5.0;
+/// A bee attacked this close to a running alarm refreshes it instead of
+/// raising another one.
+pub const ALARM_MERGE_DISTANCE: f32 = 40.0;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Alarm {
+    pub position: Vec2,
+    pub age: f32,
+}
+
+impl Alarm {
+    pub fn new(position: Vec2) -> Self {
+        Alarm { position, age: 0.0 }
+    }
+
+    /// Shrinks linearly from [`ALARM_RADIUS`] over [`ALARM_DURATION`].
+    pub fn get_radius(&self) -> f32 {
+        ALARM_RADIUS * (1.0 - self.age / ALARM_DURATION).max(0.0)
+    }
+}
+
+/// Alarms raised by attacked bees. The whole hive is alarmed while any of
+/// them is running.
+#[derive(Resource, Default, Clone, Debug, Serialize, Deserialize)]
+pub struct HiveAlarm {
+    pub alarms: Vec<Alarm>,
+}
+
+impl HiveAlarm {
+    pub fn is_raised(&self) -> bool {
+        !self.alarms.is_empty()
+    }
+
+    pub fn raise(&mut self, position: Vec2) {
+        let existing = self
+            .alarms
+            .iter_mut()
+            .find(|alarm| alarm.position.distance_squared(position) < ALARM_MERGE_DISTANCE.powi(2));
+        match existing {
+            Some(alarm) => *alarm = Alarm::new(position),
+            None => self.alarms.push(Alarm::new(position)),
+        }
+    }
+
+    /// Position of the closest alarm heard at `position`. Bees hear alarms
+    /// `alert_distance` beyond their radius.
+    pub fn get_heard_alarm(&self, position: Vec2, alert_distance: f32) -> Option<Vec2> {
+        self.alarms
+            .iter()
+            .filter(|alarm| {
+                alarm.position.distance_squared(position)
+                    < (alarm.get_radius() + alert_distance).powi(2)
+            })
+            .map(|alarm| alarm.position)
+            .min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            })
+    }
+}
+
+pub fn alarm_system(
+    mut hive_alarm: ResMut<HiveAlarm>,
+    creatures: Query<(&LivingCreature, &Transform, &Faction)>,
+    orders: Query<(Entity, Option<&SpawnOrder>), With<LivingCreature>>,
+    time: Res<Time>,
+) {
+    for alarm in hive_alarm.alarms.iter_mut() {
+        alarm.age += time.delta_seconds();
+    }
+    hive_alarm.alarms.retain(|alarm| alarm.age < ALARM_DURATION);
+
+    // Runs after the fight, so damage dealt this step is still fresh. Which
+    // alarms merge depends on the order they are raised in.
+    for e in get_spawn_ordered(orders.iter()) {
+        let Ok((creature, transform, faction)) = creatures.get(e) else {
+            continue;
+        };
+        if *faction == Faction::Bees
+            && creature.time_since_last_damage_taken <= time.delta_seconds()
+        {
+            hive_alarm.raise(transform.flat());
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct HiveAlarmText;
+
+/// Blinks while the hive is alarmed.
+pub fn hive_alarm_text_system(
+    mut texts: Query<&mut Visibility, With<HiveAlarmText>>,
+    hive_alarm: Res<HiveAlarm>,
+    time: Res<Time<Real>>,
+) {
+    let visible = hive_alarm.is_raised() && time.elapsed_seconds().fract() < 0.6;
+    for mut visibility in texts.iter_mut() {
+        let new_visibility = if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != new_visibility {
+            *visibility = new_visibility;
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::core::{BeeType, CurrencyGainPerMinute, EnemyType, LivingCreature, UniversalMaterial, GameInfo};
+use crate::core::{get_universal_material_mut, BeeType, CurrencyGainPerMinute, EnemyType, LivingCreature, UniversalMaterial, UnitRegistry};
 
 use super::UniversalBehaviour;
 
@@ -11,26 +11,24 @@ pub fn baby_behaviour_system(
             &mut LivingCreature,
             &mut UniversalBehaviour,
             &mut CurrencyGainPerMinute,
-            &Handle<UniversalMaterial>,
+            Option<&Handle<UniversalMaterial>>,
         ),
         Without<EnemyType>,
     >,
-    mut materials: ResMut<Assets<UniversalMaterial>>,
+    mut materials: Option<ResMut<Assets<UniversalMaterial>>>,
     time: Res<Time>,
-    game: Res<GameInfo>,
+    units: Res<UnitRegistry>,
 ) {
-    if game.paused {
-        return;
-    }
     for (mut bee, mut living_creature, mut behaviour, mut gain, material) in bees.iter_mut() {
         match *bee {
             BeeType::Baby => {
                 if living_creature.time_alive > 15.0 {
                     *bee = BeeType::Regular;
-                    *living_creature = LivingCreature::from(BeeType::Regular);
-                    *behaviour = UniversalBehaviour::from(BeeType::Regular);
-                    *gain = CurrencyGainPerMinute::from(BeeType::Regular);
-                    if let Some(material) = materials.get_mut(material) {
+                    let stats = units.get(BeeType::Regular);
+                    *living_creature = stats.get_creature();
+                    *behaviour = stats.get_behaviour();
+                    *gain = stats.get_gain();
+                    if let Some(material) = get_universal_material_mut(&mut materials, material) {
                         material.props.upgrade_time = time.elapsed_seconds();
                     }
                 }
@@ -1,17 +1,36 @@
 use bevy::prelude::*;
 
+use crate::core::LogicSet;
+
+mod alarm;
 mod bees;
+mod retreat;
+mod targeting;
 mod universal_behaviour;
 
+pub use alarm::*;
 use bees::*;
+pub use retreat::*;
+pub use targeting::*;
 pub use universal_behaviour::*;
 
 pub struct BehaviourPlugin;
 
 impl Plugin for BehaviourPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, universal_behaviour_system);
-        app.add_systems(Update, baby_behaviour_system);
-        app.add_systems(Update, fight_system);
+        app.init_resource::<HiveAlarm>();
+
+        app.add_systems(
+            FixedUpdate,
+            (universal_behaviour_system, baby_behaviour_system)
+                .chain()
+                .in_set(LogicSet::Behaviour),
+        );
+        app.add_systems(
+            FixedUpdate,
+            (fight_system, alarm_system, retreat_heal_system)
+                .chain()
+                .in_set(LogicSet::Combat),
+        );
     }
 }
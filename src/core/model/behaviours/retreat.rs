@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::core::{Faction, HiveMap, LivingCreature};
+
+use super::UniversalBehaviour;
+
+/// A retreat ends once threats are this many run away radii away.
+pub const RETREAT_RELEASE_FACTOR: f32 = 1.5;
+/// Seconds without damage before a retreating creature starts to heal.
+pub const RETREAT_HEAL_DELAY: f32 = 2.0;
+/// Seconds to heal one health point while retreating.
+pub const RETREAT_HEAL_INTERVAL: f32 = 1.5;
+
+/// Bees fall back to the birther, enemies fly away from the hive.
+pub fn get_retreat_position(
+    position: Vec2,
+    threat: Vec2,
+    nexus: Vec2,
+    faction: &Faction,
+    map: &HiveMap,
+    rng: &mut impl Rng,
+) -> Vec2 {
+    match faction {
+        Faction::Bees => {
+            // The spot next to the birther furthest from the threat
+            let mut best = nexus;
+            for _ in 0..8 {
+                let pos = nexus + Vec2::new(rng.gen_range(-40.0..40.0), rng.gen_range(-40.0..40.0));
+                if map.get_obstruction(pos) > 0.0 {
+                    continue;
+                }
+                if pos.distance_squared(threat) > best.distance_squared(threat) {
+                    best = pos;
+                }
+            }
+            best
+        }
+        Faction::Enemies => {
+            let away = (position - threat).normalize_or_zero() + position.normalize_or_zero();
+            position + away.normalize_or_zero() * 150.0
+        }
+    }
+}
+
+pub fn retreat_heal_system(
+    mut creatures: Query<(&mut UniversalBehaviour, &mut LivingCreature)>,
+    time: Res<Time>,
+) {
+    for (mut behaviour, mut creature) in creatures.iter_mut() {
+        if !behaviour.retreating
+            || creature.is_dead()
+            || creature.health >= creature.max_health
+            || creature.time_since_last_damage_taken < RETREAT_HEAL_DELAY
+        {
+            if behaviour.heal_progress != 0.0 {
+                behaviour.heal_progress = 0.0;
+            }
+            continue;
+        }
+
+        behaviour.heal_progress += time.delta_seconds();
+        if behaviour.heal_progress >= RETREAT_HEAL_INTERVAL {
+            behaviour.heal_progress -= RETREAT_HEAL_INTERVAL;
+            creature.health += 1;
+        }
+    }
+}
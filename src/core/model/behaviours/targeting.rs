@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{BeeType, BuildingKind, HiveDefinition};
+
+/// How a unit picks what to attack out of everything in its attack radius.
+/// Part of the unit definition. Every strategy but [`TargetingStrategy::Buildings`]
+/// goes for units first and falls back to the nearest building.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum TargetingStrategy {
+    /// The closest one.
+    #[default]
+    Nearest,
+    /// The one with the least health left, the closest one on ties.
+    Weakest,
+    /// Workers outside the hive, anything nearest if there are none.
+    Workers,
+    /// The queen, anything nearest if it can't be reached.
+    Queen,
+    /// The nearest standing building, anything nearest if there are none.
+    Buildings,
+}
+
+/// Something a unit could attack.
+#[derive(Clone, Copy, Debug)]
+pub struct TargetCandidate {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub dist_sqr: f32,
+    pub health: i32,
+    pub bee: Option<BeeType>,
+    pub building: Option<BuildingKind>,
+}
+
+fn nearest<'a>(
+    candidates: impl Iterator<Item = &'a TargetCandidate>,
+) -> Option<&'a TargetCandidate> {
+    candidates.min_by(|a, b| a.dist_sqr.total_cmp(&b.dist_sqr))
+}
+
+impl TargetingStrategy {
+    pub fn select<'a>(
+        &self,
+        candidates: &'a [TargetCandidate],
+        hive: &HiveDefinition,
+    ) -> Option<&'a TargetCandidate> {
+        let units = || candidates.iter().filter(|candidate| candidate.building.is_none());
+        let preferred = match self {
+            TargetingStrategy::Nearest => None,
+            TargetingStrategy::Weakest => units().min_by(|a, b| {
+                a.health
+                    .cmp(&b.health)
+                    .then(a.dist_sqr.total_cmp(&b.dist_sqr))
+            }),
+            TargetingStrategy::Workers => nearest(units().filter(|candidate| {
+                matches!(candidate.bee, Some(BeeType::Worker(_)))
+                    && !hive.contains(candidate.position)
+            })),
+            TargetingStrategy::Queen => candidates
+                .iter()
+                .find(|candidate| candidate.bee == Some(BeeType::Queen)),
+            TargetingStrategy::Buildings => {
+                return nearest(
+                    candidates
+                        .iter()
+                        .filter(|candidate| candidate.building.is_some()),
+                )
+                .or_else(|| nearest(candidates.iter()));
+            }
+        };
+        preferred
+            .or_else(|| nearest(units()))
+            .or_else(|| nearest(candidates.iter()))
+    }
+}
@@ -1,14 +1,19 @@
 use bevy::prelude::*;
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand::Rng;
 
 use crate::{
     core::{
-        BeeType, EnemyType, Faction, HiveMap, LivingCreature, NavigationResult, NavigationTarget,
-        RigidBody, GameInfo,
+        BeeType, Building, EnemyType, Faction, HiveBuildings, HiveMap, BUILDING_RADIUS,
+        LivingCreature, NavigationResult, NavigationTarget, RigidBody, GameRng, RngStream, Shield,
+        SpatialEntry, SpatialGrid, get_spawn_ordered, SpawnOrder,
     },
     utils::FlatProvider,
 };
 
+use super::{
+    get_retreat_position, HiveAlarm, TargetCandidate, TargetingStrategy, RETREAT_RELEASE_FACTOR,
+};
+
 #[derive(Component)]
 pub struct UniversalBehaviour {
     pub max_wonder_distance_to_hive: f32,
@@ -18,12 +23,21 @@ pub struct UniversalBehaviour {
     pub enemy_attack_distance_to_hive: f32,
     pub enemy_attack_radius: f32,
     pub enemy_attack_radius_if_alerted: f32,
+    /// How far beyond an alarm's radius it is still heard.
     pub alert_distance: f32,
+    /// Falls back to the birther instead of fighting when alarmed.
+    pub flee_on_alarm: bool,
 
+    /// Threats closer than this are fled from, while health is below
+    /// `min_healthpoints_before_run_away`.
     pub run_away_radius: f32,
     pub min_healthpoints_before_run_away: i32,
+    pub targeting: TargetingStrategy,
 
     pub time_since_last_refresh: f32,
+
+    pub retreating: bool,
+    pub heal_progress: f32,
 }
 
 // Navigation target:
@@ -31,94 +45,6 @@ pub struct UniversalBehaviour {
 // Entity - go to entity
 //
 
-impl From<BeeType> for UniversalBehaviour {
-    fn from(value: BeeType) -> Self {
-        match value {
-            BeeType::Baby => UniversalBehaviour {
-                max_wonder_distance_to_hive: 100.0,
-                min_wonder_distance_to_hive: 0.0,
-                min_wonder_distance: 50.0,
-                enemy_attack_distance_to_hive: 0.0,
-                enemy_attack_radius: 0.0,
-                enemy_attack_radius_if_alerted: 0.0,
-                alert_distance: 0.0,
-                run_away_radius: 40.0,
-                min_healthpoints_before_run_away: 100,
-                time_since_last_refresh: 0.0,
-            },
-            BeeType::Regular => UniversalBehaviour {
-                max_wonder_distance_to_hive: 190.0,
-                min_wonder_distance_to_hive: 70.0,
-                min_wonder_distance: 60.0,
-                enemy_attack_distance_to_hive: 1000.0,
-                enemy_attack_radius: 40.0,
-                enemy_attack_radius_if_alerted: 40.0,
-                alert_distance: 0.0,
-                run_away_radius: 0.0,
-                min_healthpoints_before_run_away: 0,
-                time_since_last_refresh: 0.0,
-            },
-            BeeType::Worker(lvl) => UniversalBehaviour {
-                // todo: depends on lvl?
-                max_wonder_distance_to_hive: 340.0,
-                min_wonder_distance_to_hive: 110.0,
-                min_wonder_distance: 100.0,
-                enemy_attack_distance_to_hive: 0.0,
-                enemy_attack_radius: 0.0,
-                enemy_attack_radius_if_alerted: 0.0,
-                alert_distance: 0.0,
-                run_away_radius: 60.0,
-                min_healthpoints_before_run_away: 100,
-                time_since_last_refresh: 0.0,
-            },
-            BeeType::Defender(lvl) => UniversalBehaviour {
-                // todo: depends on lvl?
-                max_wonder_distance_to_hive: 230.0,
-                min_wonder_distance_to_hive: 110.0,
-                min_wonder_distance: 80.0,
-                enemy_attack_distance_to_hive: 1000.0,
-                enemy_attack_radius: 160.0,
-                enemy_attack_radius_if_alerted: 1000.0,
-                alert_distance: 1000.0,
-                run_away_radius: 0.0,
-                min_healthpoints_before_run_away: 0,
-                time_since_last_refresh: 0.0,
-            },
-            BeeType::Queen => UniversalBehaviour {
-                max_wonder_distance_to_hive: 110.0,
-                min_wonder_distance_to_hive: 0.0,
-                min_wonder_distance: 50.0,
-                enemy_attack_distance_to_hive: 120.0,
-                enemy_attack_radius: 1000.0,
-                enemy_attack_radius_if_alerted: 1000.0,
-                alert_distance: 0.0,
-                run_away_radius: 40.0,
-                min_healthpoints_before_run_away: 10,
-                time_since_last_refresh: 0.0,
-            },
-        }
-    }
-}
-
-impl From<EnemyType> for UniversalBehaviour {
-    fn from(value: EnemyType) -> Self {
-        match value {
-            EnemyType::Wasp(lvl) | EnemyType::Birb(lvl) | EnemyType::Bumble(lvl) => UniversalBehaviour {
-                max_wonder_distance_to_hive: 200.0,
-                min_wonder_distance_to_hive: 20.0,
-                min_wonder_distance: 90.0,
-                enemy_attack_distance_to_hive: 5000.0,
-                enemy_attack_radius: 5000.0,
-                enemy_attack_radius_if_alerted: 5000.0,
-                alert_distance: 0.0,
-                run_away_radius: 0.0,
-                min_healthpoints_before_run_away: 0,
-                time_since_last_refresh: 0.0,
-            },
-        }
-    }
-}
-
 pub fn fight_system(
     mut bees: Query<
         (
@@ -126,6 +52,7 @@ pub fn fight_system(
             &RigidBody,
             &Transform,
             &NavigationTarget,
+            Option<&mut Shield>,
         ),
         Without<EnemyType>,
     >,
@@ -135,13 +62,24 @@ pub fn fight_system(
             &RigidBody,
             &Transform,
             &NavigationTarget,
+            Option<&mut Shield>,
         ),
         With<EnemyType>,
     >,
+    mut buildings: Query<(&mut LivingCreature, &Transform), (With<Building>, Without<RigidBody>)>,
+    orders: Query<(Entity, Option<&SpawnOrder>), With<RigidBody>>,
 ) {
-    for (mut bee_creature, _, bee_transform, bee_target) in bees.iter_mut() {
+    // A hit can kill, and then the next attacker doesn't swing
+    let order = get_spawn_ordered(orders.iter());
+
+    for e in order.iter() {
+        let Ok((mut bee_creature, _, bee_transform, bee_target, _)) = bees.get_mut(*e) else {
+            continue;
+        };
         if let NavigationTarget::Entity(e, _) = bee_target {
-            if let Ok((mut enemy_creature, enemy_rb, enemy_transform, _)) = enemies.get_mut(*e) {
+            if let Ok((mut enemy_creature, enemy_rb, enemy_transform, _, enemy_shield)) =
+                enemies.get_mut(*e)
+            {
                 let dist_sqr = bee_transform
                     .flat()
                     .distance_squared(enemy_transform.flat());
@@ -150,6 +88,7 @@ pub fn fight_system(
                 if dist_sqr < bee_attack_radius.powi(2) && bee_creature.can_attack() {
                     bee_creature.attack(
                         &mut enemy_creature,
+                        enemy_shield.map(|shield| shield.into_inner()),
                         enemy_transform.flat() - bee_transform.flat(),
                     );
                 }
@@ -157,9 +96,13 @@ pub fn fight_system(
         }
     }
 
-    for (mut enemy_creature, _, enemy_transform, enemy_target) in enemies.iter_mut() {
+    for e in order.iter() {
+        let Ok((mut enemy_creature, _, enemy_transform, enemy_target, _)) = enemies.get_mut(*e)
+        else {
+            continue;
+        };
         if let NavigationTarget::Entity(e, _) = enemy_target {
-            if let Ok((mut bee_creature, bee_rb, bee_transform, _)) = bees.get_mut(*e) {
+            if let Ok((mut bee_creature, bee_rb, bee_transform, _, bee_shield)) = bees.get_mut(*e) {
                 let dist_sqr = enemy_transform
                     .flat()
                     .distance_squared(bee_transform.flat());
@@ -168,9 +111,23 @@ pub fn fight_system(
                 if dist_sqr < enemy_attack_radius.powi(2) && enemy_creature.can_attack() {
                     enemy_creature.attack(
                         &mut bee_creature,
+                        bee_shield.map(|shield| shield.into_inner()),
                         bee_transform.flat() - enemy_transform.flat(),
                     );
                 }
+            } else if let Ok((mut building_creature, building_transform)) = buildings.get_mut(*e) {
+                let dist_sqr = enemy_transform
+                    .flat()
+                    .distance_squared(building_transform.flat());
+                let enemy_attack_radius = enemy_creature.attack_radius + BUILDING_RADIUS;
+
+                if dist_sqr < enemy_attack_radius.powi(2) && enemy_creature.can_attack() {
+                    enemy_creature.attack(
+                        &mut building_creature,
+                        None,
+                        building_transform.flat() - enemy_transform.flat(),
+                    );
+                }
             }
         }
     }
@@ -187,27 +144,31 @@ pub fn universal_behaviour_system(
         &mut NavigationTarget,
         &NavigationResult,
     )>,
-    all: Query<(Entity, &LivingCreature, &Transform, &Faction)>,
+    all: Query<&LivingCreature>,
+    bee_types: Query<&BeeType>,
+    grid: Res<SpatialGrid>,
     time: Res<Time>,
-    game: Res<GameInfo>,
     map: Res<HiveMap>,
-    mut rng: Local<Option<StdRng>>,
+    hive_buildings: Res<HiveBuildings>,
+    hive_alarm: Res<HiveAlarm>,
+    mut game_rng: ResMut<GameRng>,
+    orders: Query<(Entity, Option<&SpawnOrder>), With<UniversalBehaviour>>,
 ) {
-    if game.paused {
-        return;
-    }
-    if rng.is_none() {
-        *rng = Some(StdRng::seed_from_u64(0));
-    }
-    let rng = rng.as_mut().unwrap();
+    let rng = game_rng.stream(RngStream::Behaviour);
 
     if !map.ready {
         return;
     }
 
-    for (_e, mut behaviour, creature, transform, faction, mut rb, mut navigation, result) in
-        behaviours.iter_mut()
-    {
+    let nexus = hive_buildings.get_nexus_position();
+
+    // Units draw from the rng one after another
+    for e in get_spawn_ordered(orders.iter()) {
+        let Ok((_e, mut behaviour, creature, transform, faction, mut rb, mut navigation, result)) =
+            behaviours.get_mut(e)
+        else {
+            continue;
+        };
         if creature.is_dead() {
             continue;
         }
@@ -228,45 +189,88 @@ pub fn universal_behaviour_system(
 
         behaviour.time_since_last_refresh = 0.0;
 
-        let mut nearest_enemy = None;
-        let mut nearest_enemy_dist_sqr = 1e18;
+        // Keep running a bit further than where it started, so it doesn't flip every refresh
+        let run_away_radius = if behaviour.retreating {
+            behaviour.run_away_radius * RETREAT_RELEASE_FACTOR
+        } else {
+            behaviour.run_away_radius
+        };
 
-        let mut is_alert = false;
+        let is_alive = |entity: Entity| all.get(entity).is_ok_and(|other| !other.is_dead());
+        let nearest_threat = grid
+            .find_nearest(transform.flat(), run_away_radius, |other| {
+                other.is_enemy_of(faction) && other.building.is_none() && is_alive(other.entity)
+            })
+            .map(|(other, _)| other.position);
 
-        for (other_e, other_creature, other_t, other_faction) in all.iter() {
-            if other_creature.is_dead() {
-                continue;
-            }
+        // Only bees raise alarms
+        let alarm = match faction {
+            Faction::Bees => hive_alarm.get_heard_alarm(transform.flat(), behaviour.alert_distance),
+            Faction::Enemies => None,
+        };
 
-            let dist_sqr = other_t.flat().distance_squared(transform.flat());
-            if other_faction != faction {
-                if dist_sqr < nearest_enemy_dist_sqr
-                    && other_t.flat().length_squared()
-                        < behaviour.enemy_attack_distance_to_hive.powi(2)
-                {
-                    nearest_enemy_dist_sqr = dist_sqr;
-                    nearest_enemy = Some(other_e);
-                }
-            } else {
-                if dist_sqr < behaviour.alert_distance.powi(2)
-                    && other_creature.time_since_last_damage_taken < 1.0
-                {
-                    is_alert = true;
-                }
+        let wounded =
+            creature.health < behaviour.min_healthpoints_before_run_away && nearest_threat.is_some();
+        let alarmed = behaviour.flee_on_alarm && alarm.is_some();
+        behaviour.retreating = wounded || alarmed;
+
+        if behaviour.retreating {
+            let threat = if wounded { nearest_threat } else { alarm };
+            if let Some(threat) = threat {
+                *navigation = NavigationTarget::Position(get_retreat_position(
+                    transform.flat(),
+                    threat,
+                    nexus,
+                    faction,
+                    &map,
+                    rng,
+                ));
+                continue;
             }
         }
 
-        let enemy_attack_radius = if is_alert {
+        let enemy_attack_radius = if alarm.is_some() {
             behaviour.enemy_attack_radius_if_alerted
         } else {
             behaviour.enemy_attack_radius
         };
 
-        if nearest_enemy_dist_sqr.sqrt() < enemy_attack_radius && nearest_enemy.is_some() {
-            // check if should run???
+        let is_target = |other: &SpatialEntry| {
+            other.is_enemy_of(faction)
+                && other.position.length_squared() < behaviour.enemy_attack_distance_to_hive.powi(2)
+                && is_alive(other.entity)
+        };
+        let target = match behaviour.targeting {
+            // Units first, buildings only once there are none in range
+            TargetingStrategy::Nearest => grid
+                .find_nearest(transform.flat(), enemy_attack_radius, |other| {
+                    is_target(other) && other.building.is_none()
+                })
+                .or_else(|| grid.find_nearest(transform.flat(), enemy_attack_radius, is_target))
+                .map(|(other, _)| other.entity),
+            strategy => {
+                let mut candidates = vec![];
+                grid.for_each_in_radius(transform.flat(), enemy_attack_radius, |other| {
+                    if is_target(other) {
+                        candidates.push(TargetCandidate {
+                            entity: other.entity,
+                            position: other.position,
+                            dist_sqr: other.position.distance_squared(transform.flat()),
+                            health: all.get(other.entity).map_or(0, |other| other.health),
+                            bee: bee_types.get(other.entity).ok().copied(),
+                            building: other.building,
+                        });
+                    }
+                });
+                strategy
+                    .select(&candidates, &map.hive)
+                    .map(|candidate| candidate.entity)
+            }
+        };
 
+        if let Some(target) = target {
             // go to enemy!!!!
-            *navigation = NavigationTarget::Entity(nearest_enemy.unwrap(), creature.attack_radius);
+            *navigation = NavigationTarget::Entity(target, creature.attack_radius);
         } else {
             let refresh_wonder = if let NavigationTarget::Position(_) = *navigation {
                 result.is_reached() || rb.stuck_tick > 5
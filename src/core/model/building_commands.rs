@@ -0,0 +1,313 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    spawn_building, Building, BuildingKind, CurrencyStorage, HiveBuildings, MAX_DEFENDER_LEVEL,
+    MAX_SHIELD_LEVEL, MAX_WORKER_LEVEL,
+};
+
+/// Any change to the hive, applied in the order sent. Replays record these.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BuildingCommand {
+    Build { index: usize, kind: BuildingKind },
+    Upgrade { index: usize },
+    Destroy { index: usize },
+    /// Queues one unit (or one synthesis) in the building at `index`.
+    Order { index: usize },
+}
+
+impl BuildingCommand {
+    pub fn get_index(&self) -> usize {
+        match *self {
+            BuildingCommand::Build { index, .. }
+            | BuildingCommand::Upgrade { index }
+            | BuildingCommand::Destroy { index }
+            | BuildingCommand::Order { index } => index,
+        }
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BuildRequested {
+    pub index: usize,
+    pub kind: BuildingKind,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct UpgradeRequested {
+    pub index: usize,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DestroyRequested {
+    pub index: usize,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct OrderRequested {
+    pub index: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectReason {
+    InvalidSlot,
+    SlotOccupied,
+    SlotEmpty,
+    NotEnoughCurrency,
+    StorageLimitReached,
+    CannotDestroyNexus,
+    NotUpgradable,
+    MaxLevel,
+    NotOrderable,
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectReason::InvalidSlot => write!(f, "no such building slot"),
+            RejectReason::SlotOccupied => write!(f, "slot is already built"),
+            RejectReason::SlotEmpty => write!(f, "slot is empty"),
+            RejectReason::NotEnoughCurrency => write!(f, "not enough currency"),
+            RejectReason::StorageLimitReached => write!(f, "storage limit reached"),
+            RejectReason::CannotDestroyNexus => write!(f, "the birther can't be destroyed"),
+            RejectReason::NotUpgradable => write!(f, "building has no upgrades"),
+            RejectReason::MaxLevel => write!(f, "already at max level"),
+            RejectReason::NotOrderable => write!(f, "building has nothing to order"),
+        }
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BuildingCommandSucceeded(pub BuildingCommand);
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BuildingCommandRejected {
+    pub command: BuildingCommand,
+    pub reason: RejectReason,
+}
+
+/// Requests waiting for the next simulation tick. Events only live for two
+/// frames, and a frame doesn't always run a fixed step.
+#[derive(Resource, Default)]
+pub struct BuildingCommandQueue(pub Vec<BuildingCommand>);
+
+pub fn queue_building_commands_system(
+    mut commands: EventReader<BuildingCommand>,
+    mut builds: EventReader<BuildRequested>,
+    mut upgrades: EventReader<UpgradeRequested>,
+    mut destroys: EventReader<DestroyRequested>,
+    mut orders: EventReader<OrderRequested>,
+    mut queue: ResMut<BuildingCommandQueue>,
+) {
+    queue.0.extend(commands.read().copied());
+    // Requests of different types sent in one frame are queued in this order
+    for request in builds.read() {
+        queue.0.push(BuildingCommand::Build {
+            index: request.index,
+            kind: request.kind,
+        });
+    }
+    for request in upgrades.read() {
+        queue.0.push(BuildingCommand::Upgrade { index: request.index });
+    }
+    for request in destroys.read() {
+        queue.0.push(BuildingCommand::Destroy { index: request.index });
+    }
+    for request in orders.read() {
+        queue.0.push(BuildingCommand::Order { index: request.index });
+    }
+}
+
+pub fn building_commands_system(
+    mut commands: Commands,
+    mut queue: ResMut<BuildingCommandQueue>,
+    mut buildings: Query<(Entity, &mut Building)>,
+    mut currency: ResMut<CurrencyStorage>,
+    mut hive_buildings: ResMut<HiveBuildings>,
+    mut succeeded: EventWriter<BuildingCommandSucceeded>,
+    mut rejected: EventWriter<BuildingCommandRejected>,
+) {
+    // Buildings that replace the standing ones, spawned once all commands are
+    // applied. Orders sent right after a build go to the new building.
+    let mut replaced: Vec<Option<Building>> = vec![None; hive_buildings.buildings.len()];
+
+    for command in queue.0.drain(..) {
+        let result = if command.get_index() >= hive_buildings.buildings.len() {
+            Err(RejectReason::InvalidSlot)
+        } else {
+            match command {
+                BuildingCommand::Build { index, kind } => {
+                    build(&mut hive_buildings, &mut currency, index, kind)
+                        .map(|()| replaced[index] = Some(Building::new(kind, index)))
+                }
+                BuildingCommand::Upgrade { index } => {
+                    upgrade(&mut hive_buildings, &mut currency, index)
+                }
+                BuildingCommand::Destroy { index } => {
+                    destroy(&mut hive_buildings, &mut currency, index).map(|()| {
+                        replaced[index] = Some(Building::new(BuildingKind::None, index))
+                    })
+                }
+                BuildingCommand::Order { index } => match replaced[index].as_mut() {
+                    Some(building) => order(&hive_buildings, &mut currency, building),
+                    None => {
+                        let building = buildings.iter_mut().find(|(_, b)| {
+                            b.index == index && b.kind == hive_buildings.buildings[index]
+                        });
+                        match building {
+                            Some((_, mut building)) => {
+                                order(&hive_buildings, &mut currency, &mut building)
+                            }
+                            None => Err(RejectReason::SlotEmpty),
+                        }
+                    }
+                },
+            }
+        };
+
+        match result {
+            Ok(()) => succeeded.send(BuildingCommandSucceeded(command)),
+            Err(reason) => rejected.send(BuildingCommandRejected { command, reason }),
+        }
+    }
+
+    for (e, building) in buildings.iter() {
+        if replaced.get(building.index).is_some_and(Option::is_some) {
+            commands.entity(e).despawn();
+        }
+    }
+    for building in replaced.into_iter().flatten() {
+        let position = hive_buildings.get_building_position(building.index);
+        spawn_building(&mut commands, building, position);
+    }
+}
+
+pub fn log_rejected_building_commands_system(mut rejected: EventReader<BuildingCommandRejected>) {
+    for rejection in rejected.read() {
+        info!("{:?} rejected: {}", rejection.command, rejection.reason);
+    }
+}
+
+fn build(
+    hive_buildings: &mut HiveBuildings,
+    currency: &mut CurrencyStorage,
+    index: usize,
+    kind: BuildingKind,
+) -> Result<(), RejectReason> {
+    if hive_buildings.buildings[index] != BuildingKind::None {
+        return Err(RejectReason::SlotOccupied);
+    }
+    if kind == BuildingKind::Storage
+        && hive_buildings.storages >= hive_buildings.get_max_storages()
+    {
+        return Err(RejectReason::StorageLimitReached);
+    }
+    let cost = hive_buildings.get_build_cost(kind);
+    if !currency.check_can_spend(&cost) {
+        return Err(RejectReason::NotEnoughCurrency);
+    }
+
+    currency.spend(&cost);
+    hive_buildings.buildings[index] = kind;
+    hive_buildings.any_order_done = true;
+
+    if kind == BuildingKind::Storage {
+        update_storages(hive_buildings, currency);
+    }
+    Ok(())
+}
+
+fn upgrade(
+    hive_buildings: &mut HiveBuildings,
+    currency: &mut CurrencyStorage,
+    index: usize,
+) -> Result<(), RejectReason> {
+    let kind = hive_buildings.buildings[index];
+    match kind {
+        BuildingKind::None => return Err(RejectReason::SlotEmpty),
+        BuildingKind::Workshop if hive_buildings.worker_lvl + 1 >= MAX_WORKER_LEVEL => {
+            return Err(RejectReason::MaxLevel)
+        }
+        BuildingKind::Armory if hive_buildings.defender_lvl + 1 >= MAX_DEFENDER_LEVEL => {
+            return Err(RejectReason::MaxLevel)
+        }
+        BuildingKind::Nexus if hive_buildings.shield_lvl >= MAX_SHIELD_LEVEL => {
+            return Err(RejectReason::MaxLevel)
+        }
+        BuildingKind::Workshop | BuildingKind::Armory | BuildingKind::Nexus => {}
+        _ => return Err(RejectReason::NotUpgradable),
+    }
+    let cost = hive_buildings.get_upgrade_cost(kind);
+    if !currency.check_can_spend(&cost) {
+        return Err(RejectReason::NotEnoughCurrency);
+    }
+
+    currency.spend(&cost);
+    match kind {
+        BuildingKind::Workshop => hive_buildings.worker_lvl += 1,
+        BuildingKind::Armory => hive_buildings.defender_lvl += 1,
+        BuildingKind::Nexus => hive_buildings.shield_lvl += 1,
+        _ => {}
+    }
+    hive_buildings.any_upgrade_done = true;
+    Ok(())
+}
+
+fn destroy(
+    hive_buildings: &mut HiveBuildings,
+    currency: &mut CurrencyStorage,
+    index: usize,
+) -> Result<(), RejectReason> {
+    let kind = hive_buildings.buildings[index];
+    match kind {
+        BuildingKind::None => return Err(RejectReason::SlotEmpty),
+        BuildingKind::Nexus => return Err(RejectReason::CannotDestroyNexus),
+        _ => {}
+    }
+
+    hive_buildings.buildings[index] = BuildingKind::None;
+    hive_buildings.any_order_done = true;
+
+    if kind == BuildingKind::Storage {
+        update_storages(hive_buildings, currency);
+    }
+    Ok(())
+}
+
+fn update_storages(hive_buildings: &mut HiveBuildings, currency: &mut CurrencyStorage) {
+    hive_buildings.storages = hive_buildings
+        .buildings
+        .iter()
+        .filter(|f| **f == BuildingKind::Storage)
+        .count() as u32;
+    currency.max_stored[0] = hive_buildings.get_max_honey();
+    currency.stored[0] = currency.stored[0].min(currency.max_stored[0]);
+}
+
+fn order(
+    hive_buildings: &HiveBuildings,
+    currency: &mut CurrencyStorage,
+    building: &mut Building,
+) -> Result<(), RejectReason> {
+    let mut cost = hive_buildings.get_order_cost(building.kind);
+    match building.kind {
+        BuildingKind::None => return Err(RejectReason::SlotEmpty),
+        BuildingKind::Storage => return Err(RejectReason::NotOrderable),
+        // The reactor's own currency in the cost is its output, not a price
+        BuildingKind::WaxReactor => cost[1] = 0,
+        BuildingKind::MagicWaxReactor => cost[2] = 0,
+        _ => {}
+    }
+    if !currency.check_can_spend(&cost) {
+        return Err(RejectReason::NotEnoughCurrency);
+    }
+
+    currency.spend(&cost);
+    if building.orders_count == 0 {
+        building.order_time_remaining = building.order_time;
+    }
+    building.orders_count += 1;
+    Ok(())
+}
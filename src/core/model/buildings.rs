@@ -1,45 +1,20 @@
 use bevy::{prelude::*, render::mesh::shape::Quad, sprite::Mesh2dHandle};
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    core::{AppState, BeeBundle},
+    core::{AppState, BeeBundle, Faction},
     utils::FlatProvider,
 };
 
 use super::{
-    currency, BeeType, BuildingMaterial, CurrencyGainPerMinute, CurrencyStorage, CurrencyValues,
+    currency, get_universal_material_mut, BeeType, BuildingMaterial, CurrencyGainPerMinute, CurrencyStorage, CurrencyValues,
     LivingCreature, RigidBody, UniversalBehaviour, UniversalMaterial, MAX_DEFENDER_LEVEL,
-    MAX_WORKER_LEVEL, GameInfo,
+    MAX_WORKER_LEVEL, GameRng, RngStream, HiveDefinition, UnitRegistry, assign_spawn_order,
+    get_spawn_ordered, SpawnOrder,
 };
 
-pub const HIVE_WORLD_SIZE: f32 = 320.0;
-pub const HIVE_IMAGE_SIZE: usize = 160;
-
-pub const BUILDINGS_NUM: usize = 11;
-
-pub const BUILDING_POSITIONS: [(u32, u32); BUILDINGS_NUM] = [
-    (34, 68),
-    (42, 102),
-    (54, 36),
-    (65, 66),
-    (64, 127),
-    (77, 96),
-    (88, 32),
-    (97, 69),
-    (100, 122),
-    (119, 48),
-    (118, 95),
-];
-
-pub fn get_building_position(index: usize) -> Vec2 {
-    let x =
-        ((BUILDING_POSITIONS[index].0 + 1) as f32 / HIVE_IMAGE_SIZE as f32 - 0.5) * HIVE_WORLD_SIZE;
-    let y =
-        ((BUILDING_POSITIONS[index].1 + 1) as f32 / HIVE_IMAGE_SIZE as f32 - 0.5) * HIVE_WORLD_SIZE;
-    Vec2::new(x, -y)
-}
-
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BuildingKind {
     #[default]
     None,
@@ -55,7 +30,7 @@ impl BuildingKind {
     pub fn get_menu_image(&self) -> &'static str {
         match self {
             BuildingKind::None => "images/None.png",
-            BuildingKind::Nexus => "images/NexusMenu2.png",
+            BuildingKind::Nexus => "images/NexusMenu.png",
             BuildingKind::Storage => "images/None.png",
             BuildingKind::WaxReactor => "images/WaxReactorMenu.png",
             BuildingKind::Armory => "images/ArmoryMenu.png",
@@ -66,12 +41,28 @@ impl BuildingKind {
     pub fn get_menu_size(&self) -> (u32, u32) {
         match self {
             BuildingKind::Workshop | BuildingKind::Armory => (114, 28 * 3 + 1),
-            BuildingKind::Nexus => (114, 28 + 1),
             _ => (114, 28 * 2 + 1),
         }
     }
+
+    /// Damage it takes before being destroyed, empty lots can't be attacked.
+    pub fn get_max_health(&self) -> i32 {
+        match self {
+            BuildingKind::None => 0,
+            BuildingKind::Nexus => 300,
+            BuildingKind::Storage => 120,
+            BuildingKind::WaxReactor => 100,
+            BuildingKind::Armory => 100,
+            BuildingKind::Workshop => 100,
+            BuildingKind::MagicWaxReactor => 100,
+        }
+    }
 }
 
+/// Added to the attack radius of units hitting a building, like the radius
+/// of a unit's body.
+pub const BUILDING_RADIUS: f32 = 20.0;
+
 impl ToString for BuildingKind {
     fn to_string(&self) -> String {
         match self {
@@ -98,16 +89,15 @@ pub fn get_building_image_name(kind: BuildingKind) -> &'static str {
     }
 }
 
-#[derive(Resource)]
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct HiveBuildings {
-    pub buildings: [BuildingKind; BUILDINGS_NUM],
-
-    pub build_order: Option<(BuildingKind, usize)>,
-    pub upgrade_order: Option<usize>,
-    pub destroy_order: Option<usize>,
+    pub hive: HiveDefinition,
+    /// What stands on every building slot of the hive.
+    pub buildings: Vec<BuildingKind>,
 
     pub defender_lvl: u32,
     pub worker_lvl: u32,
+    pub shield_lvl: u32,
 
     pub any_order_done: bool,
     pub any_upgrade_done: bool,
@@ -117,7 +107,8 @@ pub struct HiveBuildings {
 
 impl Default for HiveBuildings {
     fn default() -> Self {
-        let mut buildings: [BuildingKind; BUILDINGS_NUM] = Default::default();
+        let hive = HiveDefinition::default();
+        let mut buildings = vec![BuildingKind::None; hive.get_slots_num()];
         buildings[8] = BuildingKind::Nexus;
         /*buildings[6] = BuildingKind::Armory;
         buildings[4] = BuildingKind::Workshop;
@@ -127,20 +118,46 @@ impl Default for HiveBuildings {
         buildings[7] = BuildingKind::Storage;
         buildings[0] = BuildingKind::MagicWaxReactor;*/
         Self {
+            hive,
             buildings,
-            build_order: None,
-            upgrade_order: None,
-            destroy_order: None,
             any_order_done: false,
             any_upgrade_done: false,
             defender_lvl: 0,
             storages: 0,
             worker_lvl: 0,
+            shield_lvl: 0,
         }
     }
 }
 
 impl HiveBuildings {
+    /// `buildings` has one entry per building slot of `hive`.
+    pub fn from_layout(hive: HiveDefinition, buildings: Vec<BuildingKind>) -> Self {
+        assert_eq!(buildings.len(), hive.get_slots_num());
+        let storages = buildings
+            .iter()
+            .filter(|f| **f == BuildingKind::Storage)
+            .count() as u32;
+        Self {
+            hive,
+            buildings,
+            storages,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_building_position(&self, index: usize) -> Vec2 {
+        self.hive.get_slot_position(index)
+    }
+
+    /// Where the Queen lives, the middle of the hive if there is no Nexus.
+    pub fn get_nexus_position(&self) -> Vec2 {
+        self.buildings
+            .iter()
+            .position(|kind| *kind == BuildingKind::Nexus)
+            .map_or(Vec2::ZERO, |index| self.get_building_position(index))
+    }
+
     pub fn get_build_cost(&self, kind: BuildingKind) -> CurrencyValues {
         match kind {
             BuildingKind::None => CurrencyValues::default(),
@@ -180,8 +197,8 @@ impl HiveBuildings {
                 1 * (self.defender_lvl + 1) as u64,
             ],
             BuildingKind::Workshop => [
-                [6, 14, 30][self.worker_lvl as usize],
-                [1, 2, 4][self.worker_lvl as usize],
+                get_for_level(&[6, 14, 30], self.worker_lvl),
+                get_for_level(&[1, 2, 4], self.worker_lvl),
                 0,
             ],
             BuildingKind::WaxReactor => [50, 16, 0],
@@ -227,11 +244,21 @@ impl HiveBuildings {
         4
     }
 
+    /// Shield points given to a bee.
+    pub fn get_shield_capacity(&self) -> u32 {
+        get_for_level(&[0, 4, 8, 8], self.shield_lvl)
+    }
+
+    /// Seconds between two bees getting a shield.
+    pub fn get_shield_interval(&self) -> f32 {
+        get_for_level(&[0.0, 12.0, 12.0, 6.0], self.shield_lvl)
+    }
+
     pub fn get_upgrade_name(&self, kind: BuildingKind) -> &'static str {
         match kind {
-            BuildingKind::Nexus => "Random shields",
-            BuildingKind::Armory => ["Research Rambo bee", "Research Cybernetic defender", ""][self.defender_lvl as usize],
-            BuildingKind::Workshop => ["Research Crazy worker", "Research Robo worker", ""][self.worker_lvl as usize],
+            BuildingKind::Nexus => get_for_level(&["Random shields", "Stronger shields", "Frequent shields", ""], self.shield_lvl),
+            BuildingKind::Armory => get_for_level(&["Research Rambo bee", "Research Cybernetic defender", ""], self.defender_lvl),
+            BuildingKind::Workshop => get_for_level(&["Research Crazy worker", "Research Robo worker", ""], self.worker_lvl),
             _ => "",
         }
     }
@@ -239,8 +266,8 @@ impl HiveBuildings {
     pub fn get_order_name(&self, kind: BuildingKind) -> &'static str {
         match kind {
             BuildingKind::Nexus => "Baby bee",
-            BuildingKind::Armory => ["Defender", "Rambo bee", "Cybernetic defender"][self.defender_lvl as usize],
-            BuildingKind::Workshop => ["Worker", "Crazy worker", "Roboworker"][self.worker_lvl as usize],
+            BuildingKind::Armory => get_for_level(&["Defender", "Rambo bee", "Cybernetic defender"], self.defender_lvl),
+            BuildingKind::Workshop => get_for_level(&["Worker", "Crazy worker", "Roboworker"], self.worker_lvl),
             BuildingKind::WaxReactor => "Wax synthesis",
             BuildingKind::MagicWaxReactor => "Magic wax synthesis",
             _ => "",
@@ -248,7 +275,12 @@ impl HiveBuildings {
     }
 }
 
-#[derive(Component)]
+/// Value for `level`, levels past the end of `values` keep the last one.
+fn get_for_level<T: Copy>(values: &[T], level: u32) -> T {
+    values[(level as usize).min(values.len() - 1)]
+}
+
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Building {
     pub kind: BuildingKind,
     pub index: usize,
@@ -257,33 +289,140 @@ pub struct Building {
     pub order_time_remaining: f32,
 
     pub orders_count: u32,
-
-    pub orders_stashed_count: u32,
 }
 
 impl Building {
-    pub fn order(&mut self) {
-        self.orders_stashed_count += 1
+    pub fn new(kind: BuildingKind, index: usize) -> Self {
+        Building {
+            kind,
+            index,
+            order_time: if kind == BuildingKind::Nexus {
+                1.0 // todo: ok ok not 3 sec
+            } else {
+                5.0
+            },
+            order_time_remaining: 0.0,
+            orders_count: 0,
+        }
     }
 }
 
-pub fn update_buildings_system(
+/// Keeps one [`Building`] entity per slot of [`HiveBuildings`].
+pub fn sync_buildings_system(
     mut commands: Commands,
     buildings: Res<HiveBuildings>,
-    buildings_query: Query<(Entity, &Building, &Handle<BuildingMaterial>)>,
-    asset_server: Res<AssetServer>,
-    mut materials: ResMut<Assets<BuildingMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    buildings_query: Query<(Entity, &Building)>,
     state: Res<State<AppState>>,
 ) {
-    let mut exists = [false; BUILDINGS_NUM];
-    for (e, building, material) in buildings_query.iter() {
-        if building.kind != buildings.buildings[building.index] || *state.get() != AppState::InGame
+    let mut exists = vec![false; buildings.buildings.len()];
+    for (e, building) in buildings_query.iter() {
+        if buildings.buildings.get(building.index) != Some(&building.kind)
+            || *state.get() != AppState::InGame
         {
             commands.entity(e).despawn();
         } else {
             exists[building.index] = true;
         }
+    }
+
+    for index in 0..buildings.buildings.len() {
+        if exists[index] || *state.get() != AppState::InGame {
+            continue;
+        }
+
+        spawn_building(
+            &mut commands,
+            Building::new(buildings.buildings[index], index),
+            buildings.get_building_position(index),
+        );
+    }
+}
+
+pub fn spawn_building(commands: &mut Commands, building: Building, position: Vec2) -> Entity {
+    let position = position.extend(-5.0);
+    let health = building.kind.get_max_health();
+
+    let e = commands.spawn((
+        building,
+        TransformBundle::from_transform(Transform::from_translation(position)),
+        VisibilityBundle::default(),
+    )).add(assign_spawn_order).id();
+    if health > 0 {
+        commands.entity(e).insert((
+            LivingCreature {
+                health,
+                max_health: health,
+                ..default()
+            },
+            Faction::Bees,
+        ));
+    }
+    e
+}
+
+/// Buildings that lost all their health revert to an empty lot, their queued
+/// orders are lost with them.
+pub fn building_destroyed_system(
+    mut commands: Commands,
+    buildings: Query<(Entity, &Building, &LivingCreature)>,
+    mut hive_buildings: ResMut<HiveBuildings>,
+    mut currency: ResMut<CurrencyStorage>,
+) {
+    for (e, building, creature) in buildings.iter() {
+        // Already replaced by a command this step
+        let standing = hive_buildings.buildings.get(building.index) == Some(&building.kind);
+        if !creature.is_dead() || !standing {
+            continue;
+        }
+
+        commands.entity(e).despawn();
+        hive_buildings.buildings[building.index] = BuildingKind::None;
+        hive_buildings.any_order_done = true;
+
+        if building.kind == BuildingKind::Storage {
+            hive_buildings.storages = hive_buildings
+                .buildings
+                .iter()
+                .filter(|f| **f == BuildingKind::Storage)
+                .count() as u32;
+            currency.max_stored[0] = hive_buildings.get_max_honey();
+            currency.stored[0] = currency.stored[0].min(currency.max_stored[0]);
+        }
+    }
+}
+
+pub fn update_buildings_system(
+    mut commands: Commands,
+    buildings_query: Query<(
+        Entity,
+        &Building,
+        Option<&LivingCreature>,
+        Option<&Handle<BuildingMaterial>>,
+    )>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<BuildingMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (e, building, creature, material) in buildings_query.iter() {
+        let Some(material) = material else {
+            let texture = asset_server.load(get_building_image_name(building.kind));
+            let background = asset_server.load("images/BuildingProgress.png");
+            let selected = asset_server.load("images/BuildingSelected.png");
+            let hovered = asset_server.load("images/BuildingHovered.png");
+
+            commands.entity(e).insert((
+                materials.add(BuildingMaterial {
+                    progress: Vec4::ZERO,
+                    texture: Some(texture),
+                    background: Some(background),
+                    selected: Some(selected),
+                    hovered: Some(hovered),
+                    state: UVec4::ZERO,
+                }),
+                Mesh2dHandle(meshes.add(Quad::new(Vec2::new(64.0, 64.0)).into())),
+            ));
+            continue;
+        };
 
         if let Some(material) = materials.get_mut(material) {
             if building.order_time_remaining > 0.0 || building.orders_count > 0 {
@@ -292,163 +431,46 @@ pub fn update_buildings_system(
             } else {
                 material.progress = Vec4::splat(0.0);
             }
+            if let Some(creature) = creature {
+                material.progress.y =
+                    1.0 - (creature.health as f32 / creature.max_health as f32).clamp(0.0, 1.0);
+            }
         }
     }
-
-    for index in 0..BUILDINGS_NUM {
-        if exists[index] || *state.get() != AppState::InGame {
-            continue;
-        }
-
-        let texture = asset_server.load(get_building_image_name(buildings.buildings[index]));
-        let background = asset_server.load("images/BuildingProgress.png");
-        let selected = asset_server.load("images/BuildingSelected.png");
-        let hovered = asset_server.load("images/BuildingHovered.png");
-
-        commands.spawn((
-            Building {
-                kind: buildings.buildings[index],
-                index,
-                order_time: if buildings.buildings[index] == BuildingKind::Nexus {
-                    1.0 // todo: ok ok not 3 sec
-                } else {
-                    5.0
-                },
-                order_time_remaining: 0.0,
-                orders_count: 0,
-                orders_stashed_count: 0,
-            },
-            TransformBundle::from_transform(Transform::from_translation(
-                get_building_position(index).extend(-5.0),
-            )),
-            VisibilityBundle::default(),
-            materials.add(BuildingMaterial {
-                progress: Vec4::ZERO,
-                texture: Some(texture),
-                background: Some(background),
-                selected: Some(selected),
-                hovered: Some(hovered),
-                state: UVec4::ZERO,
-            }),
-            Mesh2dHandle(meshes.add(Quad::new(Vec2::new(64.0, 64.0)).into())),
-        ));
-    }
 }
 
 pub fn buildings_system(
     mut commands: Commands,
-    mut buildings: Query<(&mut Building, &Transform)>,
-    mut bee_mesh: Local<Handle<Mesh>>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    mut buildings: Query<(Entity, &mut Building, &Transform)>,
+    bee_orders: Query<(Entity, Option<&SpawnOrder>), With<BeeType>>,
     mut bees: Query<(
         &mut BeeType,
         &mut UniversalBehaviour,
         &mut LivingCreature,
         &mut RigidBody,
         &mut CurrencyGainPerMinute,
-        &Handle<UniversalMaterial>,
+        Option<&Handle<UniversalMaterial>>,
     )>,
-    mut materials: ResMut<Assets<UniversalMaterial>>,
+    mut materials: Option<ResMut<Assets<UniversalMaterial>>>,
     time: Res<Time>,
-    game: Res<GameInfo>,
-    mut rng: Local<Option<StdRng>>,
+    mut game_rng: ResMut<GameRng>,
     mut currency: ResMut<CurrencyStorage>,
-    mut hive_buildings: ResMut<HiveBuildings>,
+    hive_buildings: Res<HiveBuildings>,
+    units: Res<UnitRegistry>,
 ) {
-    if game.paused {
-        return;
-    }
-    if rng.is_none() {
-        *rng = Some(StdRng::seed_from_u64(0));
-    }
-    let rng = rng.as_mut().unwrap();
-
-    if let Some(build_order) = hive_buildings.build_order.take() {
-        let cost = hive_buildings.get_build_cost(build_order.0);
-        if currency.check_can_spend(&cost)
-            && (build_order.0 != BuildingKind::Storage
-                || hive_buildings.storages < hive_buildings.get_max_storages())
-        {
-            currency.spend(&cost);
-            hive_buildings.buildings[build_order.1] = build_order.0;
-            hive_buildings.any_order_done = true;
-
-            if build_order.0 == BuildingKind::Storage {
-                let storages = hive_buildings
-                    .buildings
-                    .iter()
-                    .filter(|f| **f == BuildingKind::Storage)
-                    .count() as u32;
-                hive_buildings.storages = storages;
-                currency.max_stored[0] = hive_buildings.get_max_honey();
-            }
-        }
-    }
-
-    if let Some(upgrade_order) = hive_buildings.upgrade_order.take() {
-        let kind = hive_buildings.buildings[upgrade_order];
-        let cost = hive_buildings.get_upgrade_cost(kind);
-        if currency.check_can_spend(&cost) {
-            if kind == BuildingKind::Workshop {
-                hive_buildings.worker_lvl = (hive_buildings.worker_lvl + 1).min(MAX_WORKER_LEVEL - 1);
-                hive_buildings.any_upgrade_done = true;
-                currency.spend(&cost);
-            }
-            if kind == BuildingKind::Armory {
-                hive_buildings.defender_lvl = (hive_buildings.defender_lvl + 1).min(MAX_DEFENDER_LEVEL - 1);
-                hive_buildings.any_upgrade_done = true;
-                currency.spend(&cost);
-            }
-            if kind == BuildingKind::Nexus {
-                // todo: apply shields
-                hive_buildings.any_upgrade_done = true;
-                currency.spend(&cost);
-            }
-        }
-        /*let cost = hive_buildings.get_build_cost(build_order.0);
-        if currency.check_can_spend(&cost) {
-            currency.spend(&cost);
-            hive_buildings.buildings[build_order.1] = build_order.0;
-            any_order_done = true;
-        }*/
-    }
-
-    if let Some(destroy_order) = hive_buildings.destroy_order.take() {
-        if hive_buildings.buildings[destroy_order] != BuildingKind::Nexus {
-            hive_buildings.buildings[destroy_order] = BuildingKind::None;
-            hive_buildings.any_order_done = true;
-        }
-    }
-
-    if *bee_mesh == Handle::default() {
-        *bee_mesh = meshes.add(Quad::new(Vec2::new(24.0, 24.0)).into());
-    }
-
-    for (mut building, transform) in buildings.iter_mut() {
-        while building.orders_stashed_count > 0 {
-            building.orders_stashed_count -= 1;
+    let rng = game_rng.stream(RngStream::Buildings);
 
-            let mut cost = hive_buildings.get_order_cost(building.kind);
-
-            if building.kind == BuildingKind::WaxReactor {
-                cost[1] = 0;
-            }
-            if building.kind == BuildingKind::MagicWaxReactor {
-                cost[2] = 0;
-            }
-
-            if !currency.check_can_spend(&cost) {
-                break;
-            }
-
-            currency.spend(&cost);
-
-            if building.orders_count == 0 {
-                building.order_time_remaining = building.order_time;
-            }
-            building.orders_count += 1;
-        }
+    // Buildings draw from the rng and bees are converted first come first served,
+    // both in a fixed order
+    let mut slots: Vec<(usize, Entity)> =
+        buildings.iter().map(|(e, building, _)| (building.index, e)).collect();
+    slots.sort_unstable();
+    let bee_order = get_spawn_ordered(bee_orders.iter());
 
+    for (_, e) in slots {
+        let Ok((_, mut building, transform)) = buildings.get_mut(e) else {
+            continue;
+        };
         if building.orders_count == 0 {
             continue;
         }
@@ -469,10 +491,13 @@ pub fn buildings_system(
                 while x * x + y * y > 20.0 * 20.0 {
                     (x, y) = (rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0));
                 }
-                commands.spawn(BeeBundle::from((
-                    BeeType::Baby,
-                    Vec2::new(x, y) + transform.flat(),
-                )));
+                commands
+                    .spawn(BeeBundle::new(
+                        BeeType::Baby,
+                        (Vec2::new(x, y) + transform.flat()).extend(rng.gen_range(0.0..1.0)),
+                        &units,
+                    ))
+                    .add(assign_spawn_order);
                 success = true;
             }
             BuildingKind::Storage => {},
@@ -489,18 +514,22 @@ pub fn buildings_system(
                 success = true;
             }
             BuildingKind::Armory => {
-                for (mut bee, mut behaviour, mut creature, mut rb, mut gain, material) in
-                    bees.iter_mut()
-                {
+                for e in bee_order.iter() {
+                    let Ok((mut bee, mut behaviour, mut creature, mut rb, mut gain, material)) =
+                        bees.get_mut(*e)
+                    else {
+                        continue;
+                    };
                     if *bee == BeeType::Regular && !creature.is_dead() {
                         let b = BeeType::Defender(hive_buildings.defender_lvl);
                         *bee = b;
-                        *behaviour = UniversalBehaviour::from(b);
-                        *creature = LivingCreature::from(b);
-                        *rb = RigidBody::from(b);
-                        *gain = CurrencyGainPerMinute::from(b);
+                        let stats = units.get(b);
+                        *behaviour = stats.get_behaviour();
+                        *creature = stats.get_creature();
+                        *rb = stats.get_rigid_body();
+                        *gain = stats.get_gain();
                         success = true;
-                        if let Some(material) = materials.get_mut(material) {
+                        if let Some(material) = get_universal_material_mut(&mut materials, material) {
                             material.props.upgrade_time = time.elapsed_seconds();
                         }
                         break;
@@ -508,17 +537,21 @@ pub fn buildings_system(
                 }
             }
             BuildingKind::Workshop => {
-                for (mut bee, mut behaviour, mut creature, mut rb, mut gain, material) in
-                    bees.iter_mut()
-                {
+                for e in bee_order.iter() {
+                    let Ok((mut bee, mut behaviour, mut creature, mut rb, mut gain, material)) =
+                        bees.get_mut(*e)
+                    else {
+                        continue;
+                    };
                     if *bee == BeeType::Regular && !creature.is_dead() {
                         let b = BeeType::Worker(hive_buildings.worker_lvl);
                         *bee = b;
-                        *behaviour = UniversalBehaviour::from(b);
-                        *creature = LivingCreature::from(b);
-                        *rb = RigidBody::from(b);
-                        *gain = CurrencyGainPerMinute::from(b);
-                        if let Some(material) = materials.get_mut(material) {
+                        let stats = units.get(b);
+                        *behaviour = stats.get_behaviour();
+                        *creature = stats.get_creature();
+                        *rb = stats.get_rigid_body();
+                        *gain = stats.get_gain();
+                        if let Some(material) = get_universal_material_mut(&mut materials, material) {
                             material.props.upgrade_time = time.elapsed_seconds();
                         }
                         success = true;
@@ -545,11 +578,12 @@ pub fn buildings_system(
             if *bee != expected_bee && !creature.is_dead() {
                 let b = expected_bee;
                 *bee = b;
-                *behaviour = UniversalBehaviour::from(b);
-                *creature = LivingCreature::from(b);
-                *rb = RigidBody::from(b);
-                *gain = CurrencyGainPerMinute::from(b);
-                if let Some(material) = materials.get_mut(material) {
+                let stats = units.get(b);
+                *behaviour = stats.get_behaviour();
+                *creature = stats.get_creature();
+                *rb = stats.get_rigid_body();
+                *gain = stats.get_gain();
+                if let Some(material) = get_universal_material_mut(&mut materials, material) {
                     material.props.upgrade_time = time.elapsed_seconds();
                 }
             }
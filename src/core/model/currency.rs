@@ -1,8 +1,8 @@
 use crate::utils;
 
-use super::{BeeType, GameInfo};
 use bevy::{prelude::*, utils::HashMap};
 use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 
 #[derive(Eq, PartialEq, Hash, Component, Clone)]
@@ -43,7 +43,7 @@ impl Default for CurrencyValue {
 
 pub type CurrencyValues = [u64; CURRENCY_NUM];
 
-#[derive(Resource)]
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct CurrencyStorage {
     pub stored: CurrencyValues,
     pub max_stored: CurrencyValues,
@@ -88,7 +88,7 @@ impl Default for CurrencyStorage {
     }
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct CurrencyGainPerMinute {
     pub gain: CurrencyValues,
     pub gained_this_minute: CurrencyValues,
@@ -99,11 +99,7 @@ pub fn gain_system(
     mut currency: ResMut<CurrencyStorage>,
     mut gainers: Query<&mut CurrencyGainPerMinute>,
     time: Res<Time>,
-    game: Res<GameInfo>,
 ) {
-    if game.paused {
-        return;
-    }
     currency.estimated_inflow = [0; CURRENCY_NUM];
     for mut gainer in gainers.iter_mut() {
         currency
@@ -132,29 +128,3 @@ pub fn gain_system(
         }
     }
 }
-
-impl From<BeeType> for CurrencyGainPerMinute {
-    fn from(value: BeeType) -> Self {
-        match value {
-            BeeType::Baby => CurrencyGainPerMinute {
-                ..Default::default()
-            },
-            BeeType::Regular => CurrencyGainPerMinute {
-                gain: [1, 0, 0],
-                ..Default::default()
-            },
-            BeeType::Worker(lvl) => CurrencyGainPerMinute {
-                // todo: depends on lvl
-                gain: [[5, 3, 0], [10, 6, 0], [20, 10, 0]][lvl as usize],
-                ..Default::default()
-            },
-            BeeType::Defender(lvl) => CurrencyGainPerMinute {
-                ..Default::default()
-            },
-            BeeType::Queen => CurrencyGainPerMinute {
-                gain: [12, 8, 0],
-                ..Default::default()
-            },
-        }
-    }
-}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use super::{LivingCreature, RigidBody, Shield};
+
+/// Chance for an elite to be a champion with two affixes instead of one.
+pub const CHAMPION_CHANCE: f64 = 0.25;
+pub const ARMORED_DAMAGE_REDUCTION: u32 = 2;
+pub const SWIFT_SPEED_MULTIPLIER: f32 = 1.35;
+pub const VAMPIRIC_LIFE_STEAL: f32 = 1.0;
+/// Extra part of the drop for every affix.
+pub const AFFIX_DROP_BONUS: f32 = 0.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, EnumIter, Serialize, Deserialize)]
+pub enum EliteAffix {
+    /// Takes less damage from every hit.
+    Armored,
+    Swift,
+    /// Heals for the damage it deals.
+    Vampiric,
+    /// Falls apart into two smaller wasps on death.
+    Splitting,
+    /// Spawns with a [`Shield`].
+    Shielded,
+}
+
+impl EliteAffix {
+    pub fn get_color(&self) -> Color {
+        match self {
+            EliteAffix::Armored => Color::rgb(0.7, 0.75, 0.85),
+            EliteAffix::Swift => Color::rgb(0.65, 1.0, 0.6),
+            EliteAffix::Vampiric => Color::rgb(1.0, 0.45, 0.45),
+            EliteAffix::Splitting => Color::rgb(0.85, 0.6, 1.0),
+            EliteAffix::Shielded => Color::rgb(0.55, 0.8, 1.0),
+        }
+    }
+}
+
+/// Enemy rolled with extra affixes, a champion if it has more than one.
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Elite {
+    pub affixes: Vec<EliteAffix>,
+}
+
+impl Elite {
+    pub fn new(affixes: impl IntoIterator<Item = EliteAffix>) -> Self {
+        Elite {
+            affixes: affixes.into_iter().collect(),
+        }
+    }
+
+    pub fn roll(rng: &mut impl Rng) -> Self {
+        let count = if rng.gen_bool(CHAMPION_CHANCE) { 2 } else { 1 };
+        let all: Vec<EliteAffix> = EliteAffix::iter().collect();
+        Elite::new(all.choose_multiple(rng, count).copied())
+    }
+
+    pub fn has(&self, affix: EliteAffix) -> bool {
+        self.affixes.contains(&affix)
+    }
+
+    pub fn is_champion(&self) -> bool {
+        self.affixes.len() > 1
+    }
+
+    /// Average color of the affixes, multiplied into the unit's sprite.
+    pub fn get_tint(&self) -> Color {
+        if self.affixes.is_empty() {
+            return Color::WHITE;
+        }
+        let count = self.affixes.len() as f32;
+        let (r, g, b) = self
+            .affixes
+            .iter()
+            .fold((0.0, 0.0, 0.0), |(r, g, b), affix| {
+                let color = affix.get_color();
+                (r + color.r(), g + color.g(), b + color.b())
+            });
+        Color::rgb(r / count, g / count, b / count)
+    }
+
+    /// Applies the affixes to a freshly spawned enemy.
+    pub fn apply(&self, creature: &mut LivingCreature, rigid_body: &mut RigidBody) {
+        if self.has(EliteAffix::Armored) {
+            creature.armor += ARMORED_DAMAGE_REDUCTION;
+        }
+        if self.has(EliteAffix::Swift) {
+            rigid_body.max_valocity *= SWIFT_SPEED_MULTIPLIER;
+            rigid_body.max_acceleartion *= SWIFT_SPEED_MULTIPLIER;
+        }
+        if self.has(EliteAffix::Vampiric) {
+            creature.life_steal += VAMPIRIC_LIFE_STEAL;
+        }
+
+        let multiplier = 1.0 + AFFIX_DROP_BONUS * self.affixes.len() as f32;
+        creature.currency_drop = creature
+            .currency_drop
+            .map(|value| (value as f32 * multiplier).round() as u64);
+    }
+
+    pub fn get_shield(&self, creature: &LivingCreature) -> Option<Shield> {
+        self.has(EliteAffix::Shielded)
+            .then(|| Shield::new((creature.max_health / 2).max(2) as u32))
+    }
+}
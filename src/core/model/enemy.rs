@@ -1,24 +1,45 @@
-use super::UniversalMaterial;
+use super::{get_animation_phase, Elite, UnitRegistry, UniversalMaterial};
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use strum_macros::EnumIter;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, EnumIter, Component)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, EnumIter, Component, Serialize, Deserialize)]
 pub enum EnemyType {
     Wasp(u32),
     Birb(u32),
     Bumble(u32),
 }
 
+impl EnemyType {
+    pub fn get_level(&self) -> u32 {
+        match self {
+            EnemyType::Wasp(lvl) | EnemyType::Birb(lvl) | EnemyType::Bumble(lvl) => *lvl,
+        }
+    }
+
+    pub fn with_level(&self, level: u32) -> EnemyType {
+        match self {
+            EnemyType::Wasp(_) => EnemyType::Wasp(level),
+            EnemyType::Birb(_) => EnemyType::Birb(level),
+            EnemyType::Bumble(_) => EnemyType::Bumble(level),
+        }
+    }
+}
+
 pub fn update_wasp_material_system(
     mut commands: Commands,
-    mut wasps: Query<(Entity, &EnemyType), Changed<EnemyType>>,
+    mut wasps: Query<(Entity, &EnemyType, Option<&Elite>), Or<(Changed<EnemyType>, Added<Elite>)>>,
     mut materials: ResMut<Assets<UniversalMaterial>>,
+    units: Res<UnitRegistry>,
 ) {
-    for (e, wasp) in wasps.iter_mut() {
-        commands
-            .entity(e)
-            .insert(materials.add(wasp.clone().into()));
+    for (e, wasp, elite) in wasps.iter_mut() {
+        let mut material = units.get(*wasp).get_material();
+        material.props.phase = get_animation_phase(e);
+        if let Some(elite) = elite {
+            material.props.color = elite.get_tint();
+        }
+        commands.entity(e).insert(materials.add(material));
     }
 }
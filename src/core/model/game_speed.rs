@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{AppState, RelativePixelFont, RelativePixelSized, FONT_HANDLE};
+
+/// Speed of the simulation clock. Everything gameplay related runs on
+/// [`Time<Virtual>`], so pausing and fast-forward happen in one place.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum GameSpeed {
+    Paused,
+    #[default]
+    Normal,
+    Fast,
+    Faster,
+}
+
+impl GameSpeed {
+    pub fn get_multiplier(&self) -> f32 {
+        match self {
+            GameSpeed::Paused => 0.0,
+            GameSpeed::Normal => 1.0,
+            GameSpeed::Fast => 2.0,
+            GameSpeed::Faster => 4.0,
+        }
+    }
+
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            GameSpeed::Paused => "||",
+            GameSpeed::Normal => "1x",
+            GameSpeed::Fast => "2x",
+            GameSpeed::Faster => "4x",
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct SpeedButton(pub GameSpeed);
+
+const SPEED_BUTTON_COLOR: Color = Color::WHITE;
+const SPEED_BUTTON_HOVERED_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
+const SPEED_BUTTON_SELECTED_COLOR: Color = Color::rgb(0.96, 0.7, 0.0);
+
+pub fn spawn_speed_control(commands: &mut Commands, asset_server: &AssetServer) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Percent(2.0),
+                top: Val::Percent(2.0),
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Percent(0.5),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|builder| {
+            builder.spawn((
+                NodeBundle {
+                    background_color: BackgroundColor(SPEED_BUTTON_COLOR),
+                    ..default()
+                },
+                Interaction::None,
+                UiImage::new(asset_server.load("images/Pause.png")),
+                RelativePixelSized {
+                    width: 32,
+                    height: 32,
+                },
+                SpeedButton(GameSpeed::Paused),
+            ));
+
+            for speed in [GameSpeed::Normal, GameSpeed::Fast, GameSpeed::Faster] {
+                builder
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: BackgroundColor(SPEED_BUTTON_COLOR),
+                            ..default()
+                        },
+                        Interaction::None,
+                        RelativePixelSized {
+                            width: 32,
+                            height: 32,
+                        },
+                        SpeedButton(speed),
+                    ))
+                    .with_children(|builder| {
+                        builder.spawn((
+                            TextBundle::from_section(
+                                speed.get_label(),
+                                TextStyle {
+                                    font: FONT_HANDLE,
+                                    font_size: 10.0,
+                                    color: Color::BLACK,
+                                },
+                            ),
+                            RelativePixelFont { size: 12 },
+                        ));
+                    });
+            }
+        });
+}
+
+/// Speed buttons and keyboard shortcuts: `Space` toggles pause, `1`, `2` and
+/// `3` pick 1x, 2x and 4x.
+pub fn speed_control_system(
+    mut buttons: Query<(&Interaction, &SpeedButton, &mut BackgroundColor)>,
+    keys: Res<Input<KeyCode>>,
+    mut speed: ResMut<GameSpeed>,
+    mut speed_before_pause: Local<Option<GameSpeed>>,
+) {
+    let mut requested = None;
+
+    for (interaction, button, _) in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            requested = Some(button.0);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Space) {
+        requested = Some(GameSpeed::Paused);
+    }
+    if keys.just_pressed(KeyCode::Key1) {
+        requested = Some(GameSpeed::Normal);
+    }
+    if keys.just_pressed(KeyCode::Key2) {
+        requested = Some(GameSpeed::Fast);
+    }
+    if keys.just_pressed(KeyCode::Key3) {
+        requested = Some(GameSpeed::Faster);
+    }
+
+    if let Some(requested) = requested {
+        let new_speed = if requested == GameSpeed::Paused && *speed == GameSpeed::Paused {
+            speed_before_pause.take().unwrap_or_default()
+        } else {
+            requested
+        };
+
+        if new_speed == GameSpeed::Paused {
+            *speed_before_pause = Some(*speed);
+        }
+        if *speed != new_speed {
+            *speed = new_speed;
+        }
+    }
+
+    for (interaction, button, mut color) in buttons.iter_mut() {
+        let new_color = if button.0 == *speed {
+            SPEED_BUTTON_SELECTED_COLOR
+        } else if *interaction != Interaction::None {
+            SPEED_BUTTON_HOVERED_COLOR
+        } else {
+            SPEED_BUTTON_COLOR
+        };
+        if color.0 != new_color {
+            color.0 = new_color;
+        }
+    }
+}
+
+/// Main menu always runs at normal speed, in game the clock follows [`GameSpeed`].
+pub fn apply_game_speed_system(
+    speed: Res<GameSpeed>,
+    state: Res<State<AppState>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if !speed.is_changed() && !state.is_changed() {
+        return;
+    }
+
+    let speed = if *state.get() == AppState::InGame {
+        *speed
+    } else {
+        GameSpeed::Normal
+    };
+
+    if speed == GameSpeed::Paused {
+        time.pause();
+    } else {
+        time.unpause();
+        time.set_relative_speed(speed.get_multiplier());
+    }
+}
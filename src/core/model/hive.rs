@@ -0,0 +1,159 @@
+use std::fmt;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+/// Layout of a hive: the image the walls are read from, how big it is in the
+/// world and where buildings can be placed. Loaded from `.hive.json` assets.
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct HiveDefinition {
+    /// Path to the hive image asset.
+    pub image: String,
+    /// Size of the image in pixels, building slots are given in these.
+    pub image_size: UVec2,
+    /// Size of the hive in world units, centered on the origin.
+    pub world_size: Vec2,
+    /// Opaque pixels of any other color block movement.
+    pub passable_colors: Vec<[u8; 4]>,
+    /// Pixel positions of the building lots, in the order they are indexed.
+    pub building_slots: Vec<UVec2>,
+}
+
+impl Default for HiveDefinition {
+    fn default() -> Self {
+        Self {
+            image: String::from("images/Hive.png"),
+            image_size: UVec2::new(160, 160),
+            world_size: Vec2::new(320.0, 320.0),
+            passable_colors: vec![[246, 180, 0, 255], [246, 188, 0, 255], [255, 255, 255, 0]],
+            building_slots: [
+                (34, 68),
+                (42, 102),
+                (54, 36),
+                (65, 66),
+                (64, 127),
+                (77, 96),
+                (88, 32),
+                (97, 69),
+                (100, 122),
+                (119, 48),
+                (118, 95),
+            ]
+            .into_iter()
+            .map(|(x, y)| UVec2::new(x, y))
+            .collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum HiveDefinitionLoaderError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for HiveDefinitionLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HiveDefinitionLoaderError::Io(e) => write!(f, "could not read hive: {}", e),
+            HiveDefinitionLoaderError::Json(e) => write!(f, "malformed hive: {}", e),
+            HiveDefinitionLoaderError::Invalid(reason) => write!(f, "hive is invalid: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for HiveDefinitionLoaderError {}
+
+impl From<std::io::Error> for HiveDefinitionLoaderError {
+    fn from(value: std::io::Error) -> Self {
+        HiveDefinitionLoaderError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for HiveDefinitionLoaderError {
+    fn from(value: serde_json::Error) -> Self {
+        HiveDefinitionLoaderError::Json(value)
+    }
+}
+
+impl HiveDefinition {
+    pub fn from_slice(bytes: &[u8]) -> Result<HiveDefinition, HiveDefinitionLoaderError> {
+        let hive: HiveDefinition = serde_json::from_slice(bytes)?;
+        let invalid = |reason: String| Err(HiveDefinitionLoaderError::Invalid(reason));
+
+        if hive.image_size.x == 0 || hive.image_size.y == 0 {
+            return invalid(String::from("image_size must not be empty"));
+        }
+        if !(hive.world_size.x > 0.0 && hive.world_size.y > 0.0) {
+            return invalid(format!("world_size must be positive, got {}", hive.world_size));
+        }
+        if hive.passable_colors.is_empty() {
+            return invalid(String::from("passable_colors must not be empty"));
+        }
+        if hive.building_slots.is_empty() {
+            return invalid(String::from("building_slots must not be empty"));
+        }
+        for (index, slot) in hive.building_slots.iter().enumerate() {
+            if slot.x >= hive.image_size.x || slot.y >= hive.image_size.y {
+                return invalid(format!("building slot {} at {} is outside the image", index, slot));
+            }
+        }
+
+        Ok(hive)
+    }
+
+    pub fn get_slots_num(&self) -> usize {
+        self.building_slots.len()
+    }
+
+    /// World position of an image pixel.
+    pub fn get_world_position(&self, pixel: UVec2) -> Vec2 {
+        let x = ((pixel.x + 1) as f32 / self.image_size.x as f32 - 0.5) * self.world_size.x;
+        let y = ((pixel.y + 1) as f32 / self.image_size.y as f32 - 0.5) * self.world_size.y;
+        Vec2::new(x, -y)
+    }
+
+    pub fn get_slot_position(&self, index: usize) -> Vec2 {
+        self.get_world_position(self.building_slots[index])
+    }
+
+    /// Whether a world position is within the bounds of the hive.
+    pub fn contains(&self, position: Vec2) -> bool {
+        position.abs().cmplt(self.world_size / 2.0).all()
+    }
+
+    pub fn is_passable(&self, color: [u8; 4]) -> bool {
+        color[3] != 255 || self.passable_colors.contains(&color)
+    }
+}
+
+#[derive(Default)]
+pub struct HiveDefinitionLoader;
+
+impl AssetLoader for HiveDefinitionLoader {
+    type Asset = HiveDefinition;
+    type Settings = ();
+    type Error = HiveDefinitionLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<HiveDefinition, HiveDefinitionLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            HiveDefinition::from_slice(&bytes)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["hive.json"]
+    }
+}
@@ -1,10 +1,12 @@
 pub use bevy::prelude::*;
 
+use serde::{Deserialize, Serialize};
+
 use crate::core::NavigationTarget;
 
-use super::{BeeType, EnemyType, RigidBody, UniversalMaterial, CurrencyValue, CurrencyValues, CurrencyStorage};
+use super::{get_universal_material_mut, Building, RigidBody, Shield, UniversalMaterial, CurrencyValue, CurrencyValues, CurrencyStorage};
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct LivingCreature {
     pub time_alive: f32,
 
@@ -15,6 +17,11 @@ pub struct LivingCreature {
     pub attack_radius: f32,
     pub attack_cooldown: f32,
 
+    /// Taken off every hit, a hit still does at least 1 damage.
+    pub armor: u32,
+    /// Part of the damage dealt to health that heals the attacker.
+    pub life_steal: f32,
+
     pub time_since_last_attack: f32,
     pub time_since_last_damage_taken: f32,
 
@@ -33,6 +40,8 @@ impl Default for LivingCreature {
             attack_damage: Default::default(),
             attack_cooldown: 10.0,
             attack_radius: 14.0,
+            armor: 0,
+            life_steal: 0.0,
             time_since_last_attack: Default::default(),
             time_since_last_damage_taken: 1000.,
             accumulated_push_back: Vec2::ZERO,
@@ -51,11 +60,28 @@ impl LivingCreature {
         self.time_since_last_attack > self.attack_cooldown
     }
 
-    pub fn attack(&mut self, other: &mut LivingCreature, direction: Vec2) {
+    /// Damage goes to `other_shield` first, if there is one.
+    pub fn attack(
+        &mut self,
+        other: &mut LivingCreature,
+        other_shield: Option<&mut Shield>,
+        direction: Vec2,
+    ) {
         if !other.is_dead() && self.attack_damage > 0 && self.can_attack() {
-            other.health -= self.attack_damage as i32;
+            let damage = self.attack_damage.saturating_sub(other.armor).max(1);
+            let damage = match other_shield {
+                Some(shield) => shield.absorb(damage),
+                None => damage,
+            };
             self.time_since_last_attack = 0.0;
-            other.time_since_last_damage_taken = 0.0;
+            if damage > 0 {
+                other.health -= damage as i32;
+                other.time_since_last_damage_taken = 0.0;
+                if self.life_steal > 0.0 {
+                    let healed = (damage as f32 * self.life_steal).ceil() as i32;
+                    self.health = (self.health + healed).min(self.max_health);
+                }
+            }
 
             let pb = direction.normalize_or_zero() * (self.attack_damage as f32 / other.max_health as f32).min(1.0).powf(0.6).min(3.0);
 
@@ -65,84 +91,12 @@ impl LivingCreature {
     }
 }
 
-impl From<BeeType> for LivingCreature {
-    fn from(value: BeeType) -> Self {
-        match value {
-            BeeType::Baby => LivingCreature {
-                health: 1,
-                max_health: 1,
-                attack_damage: 0,
-                attack_cooldown: 0.0,
-                ..Default::default()
-            },
-            BeeType::Regular => LivingCreature {
-                health: 2,
-                max_health: 2,
-                attack_damage: 1,
-                attack_cooldown: 1.5,
-                ..Default::default()
-            },
-            BeeType::Worker(lvl) => LivingCreature {
-                //todo:
-                health: 2 + 3 * lvl as i32,
-                max_health: 2 + 3 * lvl as i32,
-                attack_damage: 0,
-                attack_cooldown: 2.0,
-                ..Default::default()
-            },
-            BeeType::Defender(lvl) => LivingCreature {
-                health: 5 + 5 * lvl as i32,
-                max_health: 5 + 5 * lvl as i32,
-                attack_damage: 2 + 1 * lvl,
-                attack_cooldown: 2.5 - 0.4 * lvl as f32,
-                ..Default::default()
-            },
-            BeeType::Queen => LivingCreature {
-                health: 100,
-                max_health: 100,
-                attack_damage: 4,
-                attack_cooldown: 1.5,
-                end_game_on_dead: true,
-                ..Default::default()
-            },
-        }
-    }
-}
-
-impl From<EnemyType> for LivingCreature {
-    fn from(value: EnemyType) -> Self {
-        match value {
-            EnemyType::Wasp(lvl) => LivingCreature {
-                health: 8 * (lvl + 1) as i32,
-                max_health:  8 * (lvl + 1) as i32,
-                attack_damage: 1 + lvl,
-                attack_cooldown: 2.0,
-                ..Default::default()
-            },
-            EnemyType::Birb(lvl) => LivingCreature {
-                health: [40, 100, 240][lvl as usize],
-                max_health:  [40, 100, 240][lvl as usize],
-                attack_damage: [8, 12, 16][lvl as usize],
-                attack_cooldown: 2.2,
-                attack_radius: 28.0,
-                ..Default::default()
-            },
-            EnemyType::Bumble(lvl) => LivingCreature {
-                health: [80, 160, 400][lvl as usize],
-                max_health:  [80, 160, 400][lvl as usize],
-                attack_damage: [16, 20, 40][lvl as usize],
-                attack_cooldown: 4.5,
-                attack_radius: 28.0,
-                ..Default::default()
-            },
-        }
-    }
-}
-
 #[derive(Resource, Default)]
 pub struct GameInfo {
     pub end: bool,
-    pub paused: bool,
+    pub victory: bool,
+    /// The game was lost to [`LoseCondition::NexusDestroyed`](crate::levels::LoseCondition::NexusDestroyed).
+    pub nexus_destroyed: bool,
 }
 
 pub fn living_creature_system(
@@ -151,23 +105,19 @@ pub fn living_creature_system(
         &mut LivingCreature,
         Option<&Handle<UniversalMaterial>>,
         Option<&mut RigidBody>,
+        Option<&Building>,
     )>,
     mut targets: Query<&mut NavigationTarget>,
     time: Res<Time>,
-    mut materials: ResMut<Assets<UniversalMaterial>>,
+    mut materials: Option<ResMut<Assets<UniversalMaterial>>>,
     mut commands: Commands,
     mut storage: ResMut<CurrencyStorage>,
     mut game_end: ResMut<GameInfo>,
 ) {
-    if game_end.paused {
-        return;
-    }
-    for (e, mut creature, maybe_material, maybe_rb) in creatures.iter_mut() {
+    for (e, mut creature, maybe_material, maybe_rb, building) in creatures.iter_mut() {
         if creature.time_since_last_damage_taken == 0.0 {
-            if let Some(material) = maybe_material {
-                if let Some(material) = materials.get_mut(material) {
-                    material.props.damage_time = time.elapsed_seconds();
-                }
+            if let Some(material) = get_universal_material_mut(&mut materials, maybe_material) {
+                material.props.damage_time = time.elapsed_seconds();
             }
         }
 
@@ -185,7 +135,8 @@ pub fn living_creature_system(
 
         creature.accumulated_push_back = Vec2::ZERO;
 
-        if creature.is_dead() && creature.time_since_last_damage_taken > 0.8 {
+        // Destroyed buildings are left to building_destroyed_system
+        if creature.is_dead() && creature.time_since_last_damage_taken > 0.8 && building.is_none() {
             for i in 0..3 {
                 storage.stored[i] = (storage.stored[i] + creature.currency_drop[i]).min(storage.max_stored[i]);
             }
@@ -199,7 +150,7 @@ pub fn living_creature_system(
     // Clear targets to dead living creatures
     for mut target in targets.iter_mut() {
         if let NavigationTarget::Entity(e, _) = *target {
-            if let Ok((_, creature, _, _)) = creatures.get(e) {
+            if let Ok((_, creature, _, _, _)) = creatures.get(e) {
                 if creature.is_dead() {
                     *target = NavigationTarget::None;
                 }
@@ -2,9 +2,6 @@ use bevy::prelude::*;
 use bevy::render::render_asset::RenderAssets;
 use bevy::render::render_resource::{AsBindGroup, AsBindGroupShaderType, ShaderRef, ShaderType};
 use bevy::sprite::Material2d;
-use rand::Rng;
-
-use super::{BeeType, EnemyType};
 
 pub const BEE_ATLAS_HANDLE: Handle<Image> = Handle::weak_from_u128(1311196983220122547);
 pub const WASP_ATLAS_HANDLE: Handle<Image> = Handle::weak_from_u128(1311192983220225545);
@@ -24,6 +21,8 @@ pub struct BeeMaterialUniform {
     pub phase: f32,
     pub damage_time: f32,
     pub upgrade_time: f32,
+    /// Fraction of the [`super::Shield`] left, 0 without one.
+    pub shield: f32,
 }
 
 impl Default for BeeMaterialUniform {
@@ -40,6 +39,7 @@ impl Default for BeeMaterialUniform {
             overlay_x: 7,
             overlay_y: 7,
             color: Color::WHITE,
+            shield: 0.0,
         }
     }
 }
@@ -56,6 +56,7 @@ pub struct UniversalMaterial {
 
 #[derive(AsBindGroup, Debug, Clone, Reflect, Asset, Default)]
 pub struct BuildingMaterial {
+    /// x: progress of the current order, y: part of the health lost.
     #[uniform(0)]
     pub progress: Vec4,
     #[uniform(1)]
@@ -78,96 +79,6 @@ pub struct BuildingMaterial {
     pub selected: Option<Handle<Image>>,
 }
 
-impl From<BeeType> for UniversalMaterial {
-    fn from(kind: BeeType) -> Self {
-        let shape = match kind {
-            BeeType::Baby => 0,
-            BeeType::Queen => 2,
-            _ => 1,
-        };
-
-        let (overlay_x, overlay_y) = match kind {
-            BeeType::Regular => (0, 2),
-            BeeType::Worker(lvl) => (lvl, 3),
-            BeeType::Defender(lvl) => (lvl, 4),
-            BeeType::Baby => (7, 7),
-            BeeType::Queen => (7, 7),
-        };
-
-        Self {
-            props: BeeMaterialUniform {
-                color: Color::WHITE,
-                tiles_x: 8,
-                tiles_y: 8,
-                shape,
-                wing_states: 2,
-                wing_shape: shape,
-                overlay_x,
-                overlay_y,
-                phase: rand::thread_rng().gen_range(0.0..16.0),
-                damage_time: -1.0,
-                upgrade_time: -1.0,
-            },
-            texture: Some(BEE_ATLAS_HANDLE),
-        }
-    }
-}
-
-impl From<EnemyType> for UniversalMaterial {
-    fn from(kind: EnemyType) -> Self {
-        match kind {
-            EnemyType::Wasp(lvl) => UniversalMaterial {
-                props: BeeMaterialUniform {
-                    color: Color::WHITE,
-                    tiles_x: 4,
-                    tiles_y: 4,
-                    shape: 0,
-                    wing_states: 2,
-                    wing_shape: 0,
-                    overlay_x: [3, 0][lvl as usize],
-                    overlay_y: [3, 2][lvl as usize],
-                    phase: rand::thread_rng().gen_range(0.0..16.0),
-                    damage_time: -1.0,
-                    upgrade_time: -1.0,
-                },
-                texture: Some(WASP_ATLAS_HANDLE),
-            },
-            EnemyType::Birb(lvl) => UniversalMaterial {
-                props: BeeMaterialUniform {
-                    color: Color::WHITE,
-                    tiles_x: 5,
-                    tiles_y: 2,
-                    shape: 0,
-                    wing_states: 5,
-                    wing_shape: 0,
-                    overlay_x: [4, 3, 2][lvl as usize],
-                    overlay_y: 0,
-                    phase: rand::thread_rng().gen_range(0.0..16.0),
-                    damage_time: -1.0,
-                    upgrade_time: -1.0,
-                },
-                texture: Some(BIRB_ATLAS_HANDLE),
-            },
-            EnemyType::Bumble(lvl) => UniversalMaterial {
-                props: BeeMaterialUniform {
-                    color: Color::WHITE,
-                    tiles_x: 4,
-                    tiles_y: 2,
-                    shape: 0,
-                    wing_states: 2,
-                    wing_shape: 0,
-                    overlay_x: lvl,
-                    overlay_y: 0,
-                    phase: rand::thread_rng().gen_range(0.0..16.0),
-                    damage_time: -1.0,
-                    upgrade_time: -1.0,
-                },
-                texture: Some(BUMBLE_ATLAS_HANDLE),
-            },
-        }
-    }
-}
-
 impl AsBindGroupShaderType<BeeMaterialUniform> for UniversalMaterial {
     fn as_bind_group_shader_type(&self, _images: &RenderAssets<Image>) -> BeeMaterialUniform {
         self.props.clone()
@@ -196,6 +107,14 @@ impl UiMaterial for UniversalMaterial {
     }
 }
 
+/// Material of a unit, if it has one. Headless runs have no materials at all.
+pub fn get_universal_material_mut<'a>(
+    materials: &'a mut Option<ResMut<Assets<UniversalMaterial>>>,
+    handle: Option<&Handle<UniversalMaterial>>,
+) -> Option<&'a mut UniversalMaterial> {
+    materials.as_mut()?.get_mut(handle?)
+}
+
 pub fn prepare_atlases_system(
     asset_server: Res<AssetServer>,
     mut images: ResMut<Assets<Image>>,
@@ -1,43 +1,183 @@
 mod bee;
 mod behaviours;
+mod building_commands;
 mod buildings;
 mod currency;
+mod elite;
 mod enemy;
+mod game_speed;
+mod hive;
 mod living_creature;
 mod material;
 mod physcis;
+mod rng;
+mod shield;
+mod spatial_grid;
+mod spawn_order;
+mod units;
 
 pub use bee::*;
 pub use behaviours::*;
-use bevy::{prelude::*, render::mesh::shape::Quad, sprite::{Material2dPlugin, Mesh2dHandle}, utils::HashMap, ui::FocusPolicy};
+pub use building_commands::*;
+use bevy::{prelude::*, render::mesh::shape::Quad, sprite::{Material2dPlugin, Mesh2dHandle}, transform::TransformSystem, utils::HashMap, ui::FocusPolicy};
 pub use buildings::*;
 pub use currency::*;
+pub use elite::*;
 pub use enemy::*;
+pub use game_speed::*;
+pub use hive::*;
 pub use living_creature::*;
 pub use material::*;
 pub use physcis::*;
-use rand::{thread_rng, Rng};
+pub use rng::*;
+pub use shield::*;
+pub use spatial_grid::*;
+pub use spawn_order::*;
+pub use units::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::{core::{spawn_hive_visual, get_view_rect}, levels::{NextWave, Scenario0}};
+use crate::{core::{spawn_hive_visual, hive_visual_system, get_view_rect}, levels::{LoseCondition, NextWave, Scenario, ScenarioHives, ScenarioRegistry, SelectedScenario}, save::{has_autosave, PendingLoad, AUTOSAVE_PATH}};
 
 use self::behaviours::BehaviourPlugin;
 
-use super::{AppState, FONT_HANDLE, RelativePixelFont, RelativePixelSized};
+use super::{navigation_ready, AppState, LogicSet, SimulationSet, SIMULATION_HZ, FONT_HANDLE, RelativePixelFont, RelativePixelSized};
 
 pub const BEE_MESH: Handle<Mesh> = Handle::weak_from_u128(1311196983320128547);
 pub const WASP_MESH: Handle<Mesh> = Handle::weak_from_u128(1311196983120126547);
 pub const BIRB_MESH: Handle<Mesh> = Handle::weak_from_u128(1311196983520121547);
 
+/// Gameplay simulation. Doesn't need a window, a renderer or loaded images, so
+/// it can also run under `MinimalPlugins`.
 pub struct ModelPlugin;
 
 impl Plugin for ModelPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(Material2dPlugin::<UniversalMaterial>::default());
-        app.add_plugins(Material2dPlugin::<BuildingMaterial>::default());
+        app.insert_resource(Time::<Fixed>::from_hz(SIMULATION_HZ));
+        app.configure_sets(
+            FixedUpdate,
+            (SimulationSet::Logic, SimulationSet::Physics).chain(),
+        );
+        // Nothing is simulated until units can find their way, so runs don't
+        // depend on how long the navigation graph took to build
+        app.configure_sets(FixedUpdate, SimulationSet::Logic.run_if(navigation_ready));
+        app.configure_sets(
+            FixedUpdate,
+            (
+                LogicSet::Waves,
+                LogicSet::Commands,
+                LogicSet::Behaviour,
+                LogicSet::Navigation,
+                LogicSet::Combat,
+                LogicSet::Upkeep,
+            )
+                .chain()
+                .in_set(SimulationSet::Logic),
+        );
+        app.configure_sets(FixedUpdate, SimulationSet::Physics.run_if(navigation_ready));
 
         app.init_resource::<HiveBuildings>();
         app.init_resource::<CurrencyStorage>();
         app.init_resource::<GameInfo>();
+        app.init_resource::<GameRng>();
+        app.init_resource::<RunSeedOverride>();
+        app.init_resource::<SimulationTick>();
+        app.init_resource::<ShieldCooldown>();
+        app.init_resource::<SpatialGrid>();
+        app.init_resource::<SpawnCounter>();
+        app.init_resource::<GameSpeed>();
+        app.init_resource::<UnitRegistry>();
+        app.init_resource::<UnitDefinitionsHandle>();
+
+        app.init_asset::<UnitDefinitions>();
+        app.register_asset_loader(UnitDefinitionsLoader);
+        app.add_systems(Startup, load_unit_definitions_system);
+        app.add_systems(Update, apply_unit_definitions_system);
+
+        app.init_resource::<BuildingCommandQueue>();
+        app.add_event::<BuildingCommand>();
+        app.add_event::<BuildRequested>();
+        app.add_event::<UpgradeRequested>();
+        app.add_event::<DestroyRequested>();
+        app.add_event::<OrderRequested>();
+        app.add_event::<BuildingCommandSucceeded>();
+        app.add_event::<BuildingCommandRejected>();
+
+        app.add_systems(PreUpdate, queue_building_commands_system);
+
+        app.add_systems(
+            FixedUpdate,
+            (advance_simulation_tick_system, spatial_grid_system)
+                .before(SimulationSet::Logic)
+                .run_if(navigation_ready),
+        );
+        app.add_systems(
+            FixedUpdate,
+            // Buildings built this tick can already work in it
+            (
+                building_commands_system,
+                apply_deferred,
+                sync_buildings_system,
+                apply_deferred,
+                buildings_system,
+            )
+                .chain()
+                .in_set(LogicSet::Commands),
+        );
+        app.add_systems(
+            FixedUpdate,
+            (
+                gain_system,
+                shield_grant_system,
+                shield_system,
+                building_destroyed_system,
+                living_creature_system,
+            )
+                .chain()
+                .in_set(LogicSet::Upkeep),
+        );
+        app.add_systems(Update, log_rejected_building_commands_system);
+
+        app.add_systems(
+            FixedUpdate,
+            (
+                store_previous_translation_system,
+                move_to_target_system,
+                collision_system,
+                separation_system,
+                integration_system,
+            )
+                .chain()
+                .in_set(SimulationSet::Physics),
+        );
+
+        app.add_systems(
+            PostUpdate,
+            orientation_system.before(TransformSystem::TransformPropagate),
+        );
+        app.add_systems(
+            PostUpdate,
+            interpolate_translation_system.after(TransformSystem::TransformPropagate),
+        );
+
+        app.add_systems(
+            Last,
+            start_run.run_if(state_changed::<AppState>().and_then(in_state(AppState::InGame))),
+        );
+
+        app.add_systems(PostUpdate, apply_game_speed_system);
+
+        app.add_plugins(BehaviourPlugin);
+    }
+}
+
+/// Materials, meshes, menus and overlays on top of [`ModelPlugin`].
+pub struct ModelVisualsPlugin;
+
+impl Plugin for ModelVisualsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(Material2dPlugin::<UniversalMaterial>::default());
+        app.add_plugins(Material2dPlugin::<BuildingMaterial>::default());
 
         app.add_systems(Startup, create_meshes);
 
@@ -46,29 +186,18 @@ impl Plugin for ModelPlugin {
         app.add_systems(PreUpdate, update_buildings_system);
         app.add_systems(PreUpdate, prepare_atlases_system);
 
-        app.add_systems(Update, gain_system);
-        app.add_systems(Update, living_creature_system);
-        app.add_systems(Update, buildings_system);
-
-        app.add_systems(
-            PostUpdate,
-            (
-                move_to_target_system,
-                collision_system.after(move_to_target_system),
-                integration_system.after(collision_system),
-                orientation_system.after(integration_system),
-            ),
-        );
-
         app.add_systems(Last, (
             entered_main_menu.run_if(state_changed::<AppState>().and_then(in_state(AppState::MainMenu))),
-            enetered_game.run_if(state_changed::<AppState>().and_then(in_state(AppState::InGame))),
+            spawn_game_ui
+                .after(start_run)
+                .run_if(state_changed::<AppState>().and_then(in_state(AppState::InGame))),
         ));
 
         app.add_systems(Update, game_end_system.run_if(in_state(AppState::InGame)));
         app.add_systems(Update, buttons);
-
-        app.add_plugins(BehaviourPlugin);
+        app.add_systems(Update, speed_control_system.run_if(in_state(AppState::InGame)));
+        app.add_systems(Update, hive_alarm_text_system.run_if(in_state(AppState::InGame)));
+        app.add_systems(Update, hive_visual_system.run_if(in_state(AppState::InGame)));
     }
 }
 
@@ -81,12 +210,19 @@ pub fn create_meshes(mut meshes: ResMut<Assets<Mesh>>) {
 #[derive(Component)]
 pub struct Play;
 
+#[derive(Component)]
+pub struct Continue;
+
 fn entered_main_menu(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut asset_server: Res<AssetServer>,
+    mut game_rng: ResMut<GameRng>,
+    units: Res<UnitRegistry>,
 ) {
+    let rng = game_rng.stream(RngStream::Menu);
+
     commands.spawn((
         NodeBundle {
             style: Style {
@@ -101,23 +237,57 @@ fn entered_main_menu(
         },
         //UiImage::new(asset_server.load("images/MainBackground.png")),
     )).with_children(|builder| {
-        builder.spawn((
-            NodeBundle {
-                style: Style {
-                    margin: UiRect::all(Val::Auto),
-                    ..default()
-                },
-                background_color: BackgroundColor(Color::WHITE),
+        builder.spawn(NodeBundle {
+            style: Style {
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Percent(2.0),
                 ..default()
             },
-            Interaction::None,
-            RelativePixelSized {
-                width: 88 * 2,
-                height: 32 * 2
-            },
-            UiImage::new(asset_server.load("images/Play.png")),
-            Play,
-        ));
+            ..default()
+        }).with_children(|builder| {
+            builder.spawn((
+                NodeBundle {
+                    background_color: BackgroundColor(Color::WHITE),
+                    ..default()
+                },
+                Interaction::None,
+                RelativePixelSized {
+                    width: 88 * 2,
+                    height: 32 * 2
+                },
+                UiImage::new(asset_server.load("images/Play.png")),
+                Play,
+            ));
+
+            if has_autosave() {
+                builder.spawn((
+                    NodeBundle {
+                        style: Style {
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::WHITE),
+                        ..default()
+                    },
+                    Interaction::None,
+                    RelativePixelSized {
+                        width: 88 * 2,
+                        height: 32 * 2
+                    },
+                    Continue,
+                )).with_children(|builder| {
+                    builder.spawn((
+                        TextBundle::from_section("Continue", TextStyle { font: FONT_HANDLE, font_size: 10.0, color: Color::rgb(0.3, 0.23, 0.0) }),
+                        RelativePixelFont {
+                            size: 20,
+                        },
+                    ));
+                });
+            }
+        });
 
         builder.spawn((
             NodeBundle {
@@ -141,17 +311,17 @@ fn entered_main_menu(
 
     commands.spawn(
         BeeBundle {
-            ..BeeBundle::from((BeeType::Defender(1), Vec2::new(thread_rng().gen_range(-100.0..100.0), thread_rng().gen_range(-100.0..100.0))))
+            ..BeeBundle::new(BeeType::Defender(1), Vec3::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0), rng.gen_range(0.0..1.0)), &units)
         }
     );
     commands.spawn(
         BeeBundle {
-            ..BeeBundle::from((BeeType::Regular, Vec2::new(thread_rng().gen_range(-100.0..100.0), thread_rng().gen_range(-100.0..100.0))))
+            ..BeeBundle::new(BeeType::Regular, Vec3::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0), rng.gen_range(0.0..1.0)), &units)
         }
     );
     commands.spawn(
         BeeBundle {
-            ..BeeBundle::from((BeeType::Regular, Vec2::new(thread_rng().gen_range(-100.0..100.0), thread_rng().gen_range(-100.0..100.0))))
+            ..BeeBundle::new(BeeType::Regular, Vec3::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0), rng.gen_range(0.0..1.0)), &units)
         }
     );
 
@@ -167,7 +337,7 @@ fn entered_main_menu(
 pub struct BackToMenu;
 
 #[derive(Component)]
-pub struct Pause;
+pub struct RunSeedText;
 
 fn game_end_system(
     mut commands: Commands,
@@ -177,10 +347,34 @@ fn game_end_system(
     mut next_state: ResMut<NextState<AppState>>,
 ) {
     if !game_end.end {
+        *menu_spawned = false;
         return;
     }
 
     if !*menu_spawned {
+        let (title, subtitle, title_color, background) = if game_end.victory {
+            (
+                "Victory!",
+                "The hive survived",
+                Color::rgb_u8(77, 58, 0),
+                Color::rgba_u8(246, 180, 0, 238),
+            )
+        } else if game_end.nexus_destroyed {
+            (
+                "Birther destroyed...",
+                "",
+                Color::rgb(0.05, 0.02, 0.02),
+                Color::rgba_u8(40, 40, 40, 238),
+            )
+        } else {
+            (
+                "Queen died...",
+                "",
+                Color::rgb(0.05, 0.02, 0.02),
+                Color::rgba_u8(40, 40, 40, 238),
+            )
+        };
+
         commands.spawn(NodeBundle { 
             style: Style {
                 position_type: PositionType::Absolute,
@@ -193,14 +387,14 @@ fn game_end_system(
             },
             focus_policy: FocusPolicy::Block,
             z_index: ZIndex::Global(200),
-            background_color: BackgroundColor(Color::rgba_u8(40, 40, 40, 238)),
+            background_color: BackgroundColor(background),
             ..default()
         }).with_children(|builder| {
             builder.spawn(
                 (TextBundle {
-                    text: Text::from_section("Queen died...", TextStyle { font: FONT_HANDLE, font_size: 20.0, color: Color::rgb(0.05, 0.02, 0.02) }),
+                    text: Text::from_section(title, TextStyle { font: FONT_HANDLE, font_size: 20.0, color: title_color }),
                     style: Style {
-                        margin: UiRect::new(Val::Auto, Val::Auto, Val::Auto, Val::Auto),
+                        margin: UiRect::new(Val::Auto, Val::Auto, Val::Auto, Val::ZERO),
                         ..default()
                     },
                     ..default()
@@ -209,6 +403,19 @@ fn game_end_system(
                     size: 60,
                 },)
             );
+            builder.spawn(
+                (TextBundle {
+                    text: Text::from_section(subtitle, TextStyle { font: FONT_HANDLE, font_size: 20.0, color: title_color }),
+                    style: Style {
+                        margin: UiRect::new(Val::Auto, Val::Auto, Val::ZERO, Val::Auto),
+                        ..default()
+                    },
+                    ..default()
+                },
+                RelativePixelFont {
+                    size: 16,
+                },)
+            );
             builder.spawn(
                 (TextBundle {
                     text: Text::from_section("Back to menu", TextStyle { font: FONT_HANDLE, font_size: 20.0, color: Color::rgb(0.9, 0.9, 0.9) }),
@@ -235,75 +442,150 @@ fn game_end_system(
     }
 }
 
-fn enetered_game(
+/// Run seed to use instead of a random one.
+#[derive(Resource, Default)]
+pub struct RunSeedOverride(pub Option<u64>);
+
+/// Fixed steps simulated since the run started, the current one included.
+#[derive(Resource, Default, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SimulationTick(pub u64);
+
+fn advance_simulation_tick_system(mut tick: ResMut<SimulationTick>) {
+    tick.0 += 1;
+}
+
+pub fn start_run(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut asset_server: ResMut<AssetServer>,
+    asset_server: Res<AssetServer>,
     mut hive_buildings: ResMut<HiveBuildings>,
+    mut command_queue: ResMut<BuildingCommandQueue>,
     mut currency: ResMut<CurrencyStorage>,
     mut game_end: ResMut<GameInfo>,
-    mut cameras: Query<
-        (&Camera, &mut Transform)
-    >,
+    mut game_speed: ResMut<GameSpeed>,
+    registry: Res<ScenarioRegistry>,
+    selected: Res<SelectedScenario>,
+    seed_override: Res<RunSeedOverride>,
+    mut game_rng: ResMut<GameRng>,
+    mut tick: ResMut<SimulationTick>,
+    (mut shield_cooldown, mut spawn_counter): (ResMut<ShieldCooldown>, ResMut<SpawnCounter>),
+    mut hive_alarm: ResMut<HiveAlarm>,
+    (scenario_hives, hives): (Res<ScenarioHives>, Res<Assets<HiveDefinition>>),
+    units: Res<UnitRegistry>,
 ) {
-    *hive_buildings = HiveBuildings::default();
-    *currency = CurrencyStorage::default();
-    *game_end = GameInfo::default();
+    let scenario = registry.get(selected.0);
+    let hive = scenario_hives.get(selected.0, &hives).cloned().unwrap_or_else(|| {
+        warn!("Hive {} is not loaded, using the default one", scenario.hive);
+        HiveDefinition::default()
+    });
 
-    commands.spawn(BeeBundle::from((BeeType::Queen, get_building_position(8))));
+    let buildings = scenario.get_buildings(&hive);
+    *hive_buildings = HiveBuildings::from_layout(hive, buildings);
+    command_queue.0.clear();
+    *tick = SimulationTick::default();
+    *shield_cooldown = ShieldCooldown::default();
+    *spawn_counter = SpawnCounter::default();
+    *hive_alarm = HiveAlarm::default();
+    *currency = CurrencyStorage {
+        stored: scenario.currency,
+        ..default()
+    };
+    currency.max_stored[0] = hive_buildings.get_max_honey();
+    *game_end = GameInfo::default();
+    *game_speed = GameSpeed::Normal;
+    *game_rng = GameRng::new(seed_override.0.unwrap_or_else(GameRng::new_run_seed));
+    info!("Run seed: {}", game_rng.seed());
+
+    let queen_position = hive_buildings
+        .get_nexus_position()
+        .extend(game_rng.stream(RngStream::Buildings).gen_range(0.0..1.0));
+    let mut queen = BeeBundle::new(BeeType::Queen, queen_position, &units);
+    queen.creature.end_game_on_dead = scenario.lose_conditions.contains(&LoseCondition::QueenDied);
+    commands.spawn(queen).add(assign_spawn_order);
+
+    commands.spawn(Scenario {
+        index: selected.0,
+        waves: asset_server.load(&scenario.waves),
+        ..default()
+    });
+}
 
+fn spawn_game_ui(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut asset_server: ResMut<AssetServer>,
+    game_rng: Res<GameRng>,
+    hive_buildings: Res<HiveBuildings>,
+) {
     spawn_hive_visual(
         &mut commands,
         &mut meshes,
         &mut materials,
         &mut asset_server,
+        &hive_buildings.hive,
     );
 
+    spawn_speed_control(&mut commands, &asset_server);
+
     commands.spawn((
-        NodeBundle {
+        TextBundle {
+            text: Text::from_section("", TextStyle { font: FONT_HANDLE, font_size: 10.0, color: Color::BLACK }),
             style: Style {
                 position_type: PositionType::Absolute,
-                right: Val::Percent(2.0),
+                left: Val::Percent(2.0),
                 top: Val::Percent(2.0),
                 ..default()
             },
-            background_color: BackgroundColor(Color::WHITE),
             ..default()
         },
-        Interaction::None,
-        UiImage::new(asset_server.load("images/Pause.png")),
-        RelativePixelSized {
-            width: 32,
-            height: 32,
+        RelativePixelFont {
+            size: 20,
         },
-        Pause,
+        NextWave,
     ));
 
     commands.spawn((
         TextBundle {
-            text: Text::from_section("", TextStyle { font: FONT_HANDLE, font_size: 10.0, color: Color::BLACK }),
+            text: Text::from_section("The hive is under attack!", TextStyle { font: FONT_HANDLE, font_size: 10.0, color: Color::rgb(0.8, 0.1, 0.1) }),
             style: Style {
                 position_type: PositionType::Absolute,
-                left: Val::Percent(2.0),
                 top: Val::Percent(2.0),
+                margin: UiRect::horizontal(Val::Auto),
+                left: Val::ZERO,
+                right: Val::ZERO,
                 ..default()
             },
+            visibility: Visibility::Hidden,
             ..default()
         },
         RelativePixelFont {
-            size: 20,
+            size: 14,
         },
-        NextWave,
+        HiveAlarmText,
     ));
 
-    commands.spawn(Scenario0::default());
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(format!("Seed: {}", game_rng.seed()), TextStyle { font: FONT_HANDLE, font_size: 10.0, color: Color::BLACK }),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(2.0),
+                bottom: Val::Percent(2.0),
+                ..default()
+            },
+            ..default()
+        },
+        RelativePixelFont {
+            size: 8,
+        },
+        RunSeedText,
+    ));
 }
 
 fn buttons(
-    mut button: Query<(&Interaction, &mut BackgroundColor), (With<Pause>, Without<Play>, Changed<Interaction>)>,
-    mut button_play: Query<(&Interaction, &mut BackgroundColor), (With<Play>, Without<Pause>, Changed<Interaction>)>,
-    mut game: ResMut<GameInfo>,
+    mut button_play: Query<(&Interaction, &mut BackgroundColor), (With<Play>, Without<Continue>, Changed<Interaction>)>,
+    mut button_continue: Query<(&Interaction, &mut BackgroundColor), (With<Continue>, Without<Play>, Changed<Interaction>)>,
+    mut pending_load: ResMut<PendingLoad>,
     mut state: ResMut<NextState<AppState>>,
     state_cur: ResMut<State<AppState>>,
     mut cameras: Query<
@@ -311,23 +593,24 @@ fn buttons(
     >,
 )
 {
-    for (interaction, mut color) in button.iter_mut() {
+    for (interaction, mut color) in button_play.iter_mut() {
         if *interaction != Interaction::None {
-            *color = BackgroundColor(Color::rgb(0.8, 0.8, 0.8));
+            *color = BackgroundColor(Color::rgb(0.9, 0.9, 0.9));
         } else {
             *color = BackgroundColor(Color::WHITE);
         }
         if *interaction == Interaction::Pressed {
-            game.paused = !game.paused;
+            state.set(AppState::InGame);
         }
     }
-    for (interaction, mut color) in button_play.iter_mut() {
+    for (interaction, mut color) in button_continue.iter_mut() {
         if *interaction != Interaction::None {
             *color = BackgroundColor(Color::rgb(0.9, 0.9, 0.9));
         } else {
             *color = BackgroundColor(Color::WHITE);
         }
         if *interaction == Interaction::Pressed {
+            pending_load.0 = Some(AUTOSAVE_PATH.into());
             state.set(AppState::InGame);
         }
     }
@@ -1,13 +1,15 @@
 use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    core::{HiveMap, NavigationResult, NavigationTarget},
+    core::{Faction, GameRng, HiveMap, NavigationResult, NavigationTarget, RngStream},
     utils::FlatProvider,
 };
 
-use super::{BeeType, EnemyType, LivingCreature, GameInfo};
+use super::{get_spawn_ordered, BeeType, LivingCreature, SpatialGrid, SpawnOrder};
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct RigidBody {
     pub radius: f32,
     pub velocity: Vec2,
@@ -15,82 +17,47 @@ pub struct RigidBody {
     pub max_valocity: f32,
     pub max_acceleartion: f32,
     pub stuck_tick: u32,
+    /// Extra gap kept from bodies of the same faction.
+    pub separation_distance: f32,
+    /// Speed at which crowded bodies of the same faction are pushed apart,
+    /// 0 lets them stack.
+    pub separation_strength: f32,
 }
 
-impl From<BeeType> for RigidBody {
-    fn from(value: BeeType) -> Self {
-        match value {
-            BeeType::Baby => RigidBody {
-                radius: 4.0,
-                max_valocity: 40.0,
-                max_acceleartion: 250.0,
-                ..Default::default()
-            },
-            BeeType::Regular => RigidBody {
-                radius: 6.0,
-                max_valocity: 40.0,
-                max_acceleartion: 250.0,
-                ..Default::default()
-            },
-            BeeType::Worker(lvl) => RigidBody {
-                radius: 6.0,
-                max_valocity: 38.0,
-                max_acceleartion: 250.0,
-                ..Default::default()
-            },
-            BeeType::Defender(lvl) => RigidBody {
-                radius: 6.0,
-                max_valocity: 40.0,
-                max_acceleartion: 250.0,
-                ..Default::default()
-            },
-            BeeType::Queen => RigidBody {
-                radius: 8.0,
-                max_valocity: 40.0,
-                max_acceleartion: 250.0,
-                ..Default::default()
-            },
-        }
+#[derive(Component)]
+pub struct SmartOrientation;
+
+/// Translation before the last simulation step, used to interpolate rendering
+/// between fixed steps.
+#[derive(Component, Default, Clone, Copy)]
+pub struct PreviousTranslation(pub Vec3);
+
+pub fn store_previous_translation_system(
+    mut bodies: Query<(&mut PreviousTranslation, &Transform)>,
+) {
+    for (mut previous, transform) in bodies.iter_mut() {
+        previous.0 = transform.translation;
     }
 }
 
-impl From<EnemyType> for RigidBody {
-    fn from(value: EnemyType) -> Self {
-        match value {
-            EnemyType::Wasp(lvl) => RigidBody {
-                radius: 9.0,
-                max_valocity: 45.0,
-                max_acceleartion: 300.0,
-                ..Default::default()
-            },
-            EnemyType::Birb(lvl) => RigidBody {
-                radius: 13.0,
-                max_valocity: 45.0,
-                max_acceleartion: 350.0,
-                ..Default::default()
-            },
-            EnemyType::Bumble(lvl) => RigidBody {
-                radius: 13.0,
-                max_valocity: 35.0,
-                max_acceleartion: 200.0,
-                ..Default::default()
-            },
-        }
+pub fn interpolate_translation_system(
+    mut bodies: Query<(&PreviousTranslation, &Transform, &mut GlobalTransform), Without<Parent>>,
+    time: Res<Time<Fixed>>,
+) {
+    let alpha = time.overstep_percentage().min(1.0);
+    for (previous, transform, mut global_transform) in bodies.iter_mut() {
+        *global_transform = GlobalTransform::from(Transform {
+            translation: previous.0.lerp(transform.translation, alpha),
+            ..*transform
+        });
     }
 }
 
-#[derive(Component)]
-pub struct SmartOrientation;
-
 pub fn integration_system(
     mut rigid_bodies: Query<(&mut RigidBody, &mut Transform, Option<&LivingCreature>)>,
     time: Res<Time>,
-    game: Res<GameInfo>,
     map: Res<HiveMap>,
 ) {
-    if game.paused {
-        return;
-    }
     if !map.ready {
         return;
     }
@@ -135,18 +102,31 @@ pub fn integration_system(
 pub fn collision_system(
     mut a_rigid_bodies: Query<(&mut RigidBody, &Transform, &LivingCreature), With<BeeType>>,
     mut b_rigid_bodies: Query<(&mut RigidBody, &Transform, &LivingCreature), Without<BeeType>>,
+    orders: Query<(Entity, Option<&SpawnOrder>), (With<BeeType>, With<RigidBody>)>,
+    grid: Res<SpatialGrid>,
     time: Res<Time>,
-    game: Res<GameInfo>,
-    _map: Res<HiveMap>,
 ) {
-    if game.paused {
-        return;
-    }
-    for (mut arb, at, alc) in a_rigid_bodies.iter_mut() {
+    let mut contacts = Vec::new();
+    // Impulses add up in the order bees are visited
+    for e in get_spawn_ordered(orders.iter()) {
+        let Ok((mut arb, at, alc)) = a_rigid_bodies.get_mut(e) else {
+            continue;
+        };
         if alc.is_dead() {
             continue;
         }
-        for (mut brb, bt, blc) in b_rigid_bodies.iter_mut() {
+
+        contacts.clear();
+        grid.for_each_in_radius(at.flat(), arb.radius + grid.get_max_radius(), |entry| {
+            if !entry.is_bee {
+                contacts.push(entry.entity);
+            }
+        });
+
+        for e in contacts.iter() {
+            let Ok((mut brb, bt, blc)) = b_rigid_bodies.get_mut(*e) else {
+                continue;
+            };
             let dist_sqr = at.flat().distance_squared(bt.flat());
             let total_radius = arb.radius + brb.radius;
             if blc.is_dead() || dist_sqr > total_radius.powi(2) {
@@ -177,6 +157,63 @@ pub fn collision_system(
     }
 }
 
+/// Soft push between bodies of the same faction, so crowds spread out instead
+/// of stacking. Unlike [`collision_system`] it doesn't exchange momentum.
+pub fn separation_system(
+    mut bodies: Query<(Entity, &mut RigidBody, &Transform, &LivingCreature, &Faction)>,
+    orders: Query<(Entity, Option<&SpawnOrder>), With<RigidBody>>,
+    grid: Res<SpatialGrid>,
+    mut game_rng: ResMut<GameRng>,
+    mut pushes: Local<Vec<(Entity, Vec2)>>,
+) {
+    pushes.clear();
+    // Bodies on the same spot draw from the rng in this order
+    for e in get_spawn_ordered(orders.iter()) {
+        let Ok((e, rb, transform, creature, faction)) = bodies.get(e) else {
+            continue;
+        };
+        if creature.is_dead() || rb.separation_strength == 0.0 {
+            continue;
+        }
+
+        let position = transform.flat();
+        let reach = rb.radius + rb.separation_distance + grid.get_max_radius();
+        let mut push = Vec2::ZERO;
+        grid.for_each_in_radius(position, reach, |other| {
+            if other.entity == e || other.is_enemy_of(faction) || other.building.is_some() {
+                return;
+            }
+            let desired = rb.radius + other.radius + rb.separation_distance;
+            let delta = position - other.position;
+            let dist = delta.length();
+            if dist >= desired {
+                return;
+            }
+            // Bodies on the exact same spot still need to pick a side
+            let away = if dist > 0.01 {
+                delta / dist
+            } else {
+                Vec2::from_angle(
+                    game_rng
+                        .stream(RngStream::Physics)
+                        .gen_range(0.0..std::f32::consts::TAU),
+                )
+            };
+            push += away * (desired - dist) / desired * rb.separation_strength;
+        });
+
+        if push != Vec2::ZERO {
+            pushes.push((e, push.clamp_length_max(rb.max_valocity)));
+        }
+    }
+
+    for (e, push) in pushes.iter() {
+        if let Ok((_, mut rb, _, _, _)) = bodies.get_mut(*e) {
+            rb.pseudo_velocity += *push;
+        }
+    }
+}
+
 pub fn orientation_system(
     mut agents: Query<
         (&RigidBody, &mut Transform, Option<&NavigationTarget>),
@@ -232,11 +269,7 @@ pub fn move_to_target_system(
         With<MoveToNavigationTargetBehaviour>,
     >,
     time: Res<Time>,
-    game: Res<GameInfo>,
 ) {
-    if game.paused {
-        return;
-    }
     for (mut rb, transform, result, creature) in agents.iter_mut() {
         if creature.is_dead() {
             continue;
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+// One stream per system, so results don't depend on the order systems run in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RngStream {
+    Waves,
+    Buildings,
+    Behaviour,
+    Navigation,
+    Menu,
+    Shields,
+    Elites,
+    Physics,
+}
+
+pub const RNG_STREAMS_NUM: usize = 8;
+
+// StdRng's generator used directly, so stream positions can be saved
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    streams: [ChaCha12Rng; RNG_STREAMS_NUM],
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::new(0)
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: std::array::from_fn(|i| {
+                ChaCha12Rng::seed_from_u64(
+                    seed.wrapping_add((i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)),
+                )
+            }),
+        }
+    }
+
+    // SEED environment variable wins if it is set
+    pub fn new_run_seed() -> u64 {
+        std::env::var("SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(|| rand::thread_rng().gen())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut ChaCha12Rng {
+        &mut self.streams[stream as usize]
+    }
+
+    pub fn word_positions(&self) -> [u128; RNG_STREAMS_NUM] {
+        std::array::from_fn(|i| self.streams[i].get_word_pos())
+    }
+
+    pub fn restore(seed: u64, word_positions: [u128; RNG_STREAMS_NUM]) -> Self {
+        let mut game_rng = GameRng::new(seed);
+        for (stream, word_pos) in game_rng.streams.iter_mut().zip(word_positions) {
+            stream.set_word_pos(word_pos);
+        }
+        game_rng
+    }
+}
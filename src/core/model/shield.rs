@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    get_spawn_ordered, get_universal_material_mut, BeeType, GameRng, HiveBuildings,
+    LivingCreature, RngStream, SpawnOrder, UniversalMaterial,
+};
+
+pub const MAX_SHIELD_LEVEL: u32 = 3;
+
+/// Seconds without damage before a shield starts to regenerate.
+pub const SHIELD_REGEN_DELAY: f32 = 4.0;
+/// Seconds to regenerate one point of shield.
+pub const SHIELD_REGEN_INTERVAL: f32 = 1.0;
+
+/// Absorbs damage before [`LivingCreature::health`] does.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct Shield {
+    pub amount: u32,
+    pub max_amount: u32,
+    pub time_since_damage: f32,
+    pub regen_progress: f32,
+}
+
+impl Shield {
+    pub fn new(max_amount: u32) -> Self {
+        Shield {
+            amount: max_amount,
+            max_amount,
+            time_since_damage: SHIELD_REGEN_DELAY,
+            regen_progress: 0.0,
+        }
+    }
+
+    /// Returns the damage the shield couldn't absorb.
+    pub fn absorb(&mut self, damage: u32) -> u32 {
+        let absorbed = damage.min(self.amount);
+        self.amount -= absorbed;
+        self.time_since_damage = 0.0;
+        self.regen_progress = 0.0;
+        damage - absorbed
+    }
+
+    pub fn get_fraction(&self) -> f32 {
+        if self.max_amount == 0 {
+            return 0.0;
+        }
+        self.amount as f32 / self.max_amount as f32
+    }
+}
+
+/// Time left until the next bee gets a shield.
+#[derive(Resource, Default, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ShieldCooldown(pub f32);
+
+pub fn shield_grant_system(
+    mut commands: Commands,
+    bees: Query<(Entity, &LivingCreature, Option<&SpawnOrder>), (With<BeeType>, Without<Shield>)>,
+    mut shields: Query<&mut Shield>,
+    hive_buildings: Res<HiveBuildings>,
+    mut cooldown: ResMut<ShieldCooldown>,
+    mut game_rng: ResMut<GameRng>,
+    time: Res<Time>,
+) {
+    if hive_buildings.shield_lvl == 0 {
+        return;
+    }
+
+    // An upgrade also grows the shields bees already have
+    let capacity = hive_buildings.get_shield_capacity();
+    for mut shield in shields.iter_mut() {
+        if shield.max_amount < capacity {
+            shield.amount += capacity - shield.max_amount;
+            shield.max_amount = capacity;
+        }
+    }
+
+    cooldown.0 -= time.delta_seconds();
+    if cooldown.0 > 0.0 {
+        return;
+    }
+
+    // Sorted, so the same draw picks the same bee after a load or in a replay
+    let candidates = get_spawn_ordered(
+        bees.iter()
+            .filter(|(_, creature, _)| !creature.is_dead())
+            .map(|(e, _, order)| (e, order)),
+    );
+    if candidates.is_empty() {
+        return;
+    }
+
+    cooldown.0 = hive_buildings.get_shield_interval();
+    let rng = game_rng.stream(RngStream::Shields);
+    let e = candidates[rng.gen_range(0..candidates.len())];
+    commands
+        .entity(e)
+        .insert(Shield::new(hive_buildings.get_shield_capacity()));
+}
+
+pub fn shield_system(
+    mut shields: Query<(&mut Shield, Option<&Handle<UniversalMaterial>>)>,
+    mut materials: Option<ResMut<Assets<UniversalMaterial>>>,
+    time: Res<Time>,
+) {
+    for (mut shield, material) in shields.iter_mut() {
+        shield.time_since_damage += time.delta_seconds();
+        if shield.amount < shield.max_amount && shield.time_since_damage >= SHIELD_REGEN_DELAY {
+            shield.regen_progress += time.delta_seconds();
+            while shield.regen_progress >= SHIELD_REGEN_INTERVAL && shield.amount < shield.max_amount
+            {
+                shield.regen_progress -= SHIELD_REGEN_INTERVAL;
+                shield.amount += 1;
+            }
+        }
+
+        // Only touch the material when the overlay changes, every write re-uploads it
+        let fraction = shield.get_fraction();
+        let shown = materials
+            .as_ref()
+            .zip(material)
+            .and_then(|(materials, material)| materials.get(material))
+            .map(|material| material.props.shield);
+        if shown.is_some_and(|shown| shown != fraction) {
+            if let Some(material) = get_universal_material_mut(&mut materials, material) {
+                material.props.shield = fraction;
+            }
+        }
+    }
+}
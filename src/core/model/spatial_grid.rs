@@ -0,0 +1,207 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{core::Faction, utils::FlatProvider};
+
+use super::{
+    get_spawn_ordered, Building, BuildingKind, LivingCreature, RigidBody, SpawnOrder,
+    BUILDING_RADIUS,
+};
+
+// Roughly the range most neighbour queries ask for
+pub const SPATIAL_CELL_SIZE: f32 = 64.0;
+
+#[derive(Clone, Copy)]
+pub struct SpatialEntry {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub radius: f32,
+    pub is_bee: bool,
+    // Buildings are targets but don't move or collide
+    pub building: Option<BuildingKind>,
+}
+
+impl SpatialEntry {
+    pub fn is_enemy_of(&self, faction: &Faction) -> bool {
+        self.is_bee != (*faction == Faction::Bees)
+    }
+}
+
+// Rebuilt every step, creatures that die during a step stay in it until then
+#[derive(Resource)]
+pub struct SpatialGrid {
+    entries: Vec<SpatialEntry>,
+    cells: HashMap<IVec2, Vec<usize>>,
+    min_cell: IVec2,
+    max_cell: IVec2,
+    max_radius: f32,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        SpatialGrid {
+            entries: vec![],
+            cells: HashMap::default(),
+            min_cell: IVec2::MAX,
+            max_cell: IVec2::MIN,
+            max_radius: 0.0,
+        }
+    }
+}
+
+fn get_cell(position: Vec2) -> IVec2 {
+    (position / SPATIAL_CELL_SIZE).floor().as_ivec2()
+}
+
+impl SpatialGrid {
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        // Keeps the allocations, the same cells are usually filled again
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.min_cell = IVec2::MAX;
+        self.max_cell = IVec2::MIN;
+        self.max_radius = 0.0;
+    }
+
+    pub fn insert(&mut self, entry: SpatialEntry) {
+        let cell = get_cell(entry.position);
+        self.cells.entry(cell).or_default().push(self.entries.len());
+        self.entries.push(entry);
+        self.min_cell = self.min_cell.min(cell);
+        self.max_cell = self.max_cell.max(cell);
+        if entry.building.is_none() {
+            self.max_radius = self.max_radius.max(entry.radius);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Buildings don't count, nothing collides with them
+    pub fn get_max_radius(&self) -> f32 {
+        self.max_radius
+    }
+
+    fn cell_entries(&self, cell: IVec2) -> impl Iterator<Item = &SpatialEntry> {
+        self.cells
+            .get(&cell)
+            .into_iter()
+            .flatten()
+            .map(|i| &self.entries[*i])
+    }
+
+    pub fn for_each_in_radius(
+        &self,
+        center: Vec2,
+        radius: f32,
+        mut f: impl FnMut(&SpatialEntry),
+    ) {
+        if self.is_empty() {
+            return;
+        }
+        let min = get_cell(center - Vec2::splat(radius)).max(self.min_cell);
+        let max = get_cell(center + Vec2::splat(radius)).min(self.max_cell);
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                for entry in self.cell_entries(IVec2::new(x, y)) {
+                    if entry.position.distance_squared(center) <= radius.powi(2) {
+                        f(entry);
+                    }
+                }
+            }
+        }
+    }
+
+    // Searches rings of cells outwards and stops once no closer entry is possible
+    pub fn find_nearest(
+        &self,
+        center: Vec2,
+        max_distance: f32,
+        mut filter: impl FnMut(&SpatialEntry) -> bool,
+    ) -> Option<(SpatialEntry, f32)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let center_cell = get_cell(center);
+        let mut best: Option<(SpatialEntry, f32)> = None;
+        let mut best_dist_sqr = max_distance.powi(2);
+
+        // Cells further than this ring from the center cell are all empty
+        let last_ring = (self.min_cell - center_cell)
+            .abs()
+            .max((self.max_cell - center_cell).abs())
+            .max_element()
+            .min(((max_distance / SPATIAL_CELL_SIZE).ceil() as i32).saturating_add(1));
+
+        for ring in 0..=last_ring {
+            // Everything in this ring is at least this far away
+            let ring_distance = (ring - 1).max(0) as f32 * SPATIAL_CELL_SIZE;
+            if ring_distance.powi(2) > best_dist_sqr {
+                break;
+            }
+
+            for y in -ring..=ring {
+                let on_edge = y == -ring || y == ring;
+                let step = if on_edge { 1 } else { (2 * ring).max(1) as usize };
+                for x in (-ring..=ring).step_by(step) {
+                    for entry in self.cell_entries(center_cell + IVec2::new(x, y)) {
+                        let dist_sqr = entry.position.distance_squared(center);
+                        if dist_sqr < best_dist_sqr && filter(entry) {
+                            best_dist_sqr = dist_sqr;
+                            best = Some((*entry, dist_sqr));
+                        }
+                    }
+                }
+            }
+        }
+
+        best
+    }
+}
+
+pub fn spatial_grid_system(
+    mut grid: ResMut<SpatialGrid>,
+    creatures: Query<(Entity, &LivingCreature, &Transform, &RigidBody, &Faction)>,
+    orders: Query<(Entity, Option<&SpawnOrder>), With<RigidBody>>,
+    buildings: Query<(Entity, &Building, &LivingCreature, &Transform, &Faction), Without<RigidBody>>,
+) {
+    grid.clear();
+    // Insertion order breaks ties between equally near entries
+    for e in get_spawn_ordered(orders.iter()) {
+        let Ok((entity, creature, transform, rb, faction)) = creatures.get(e) else {
+            continue;
+        };
+        if creature.is_dead() {
+            continue;
+        }
+        grid.insert(SpatialEntry {
+            entity,
+            position: transform.flat(),
+            radius: rb.radius,
+            is_bee: *faction == Faction::Bees,
+            building: None,
+        });
+    }
+
+    let mut buildings: Vec<_> = buildings
+        .iter()
+        .filter(|(_, _, creature, _, _)| !creature.is_dead())
+        .collect();
+    buildings.sort_unstable_by_key(|(_, building, _, _, _)| building.index);
+    for (entity, building, _, transform, faction) in buildings {
+        grid.insert(SpatialEntry {
+            entity,
+            position: transform.flat(),
+            radius: BUILDING_RADIUS,
+            is_bee: *faction == Faction::Bees,
+            building: Some(building.kind),
+        });
+    }
+}
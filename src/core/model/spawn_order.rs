@@ -0,0 +1,38 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Position of a unit in the order units were spawned in during the run.
+///
+/// Queries iterate in storage order, which changes with frame pacing (when
+/// visuals are inserted) and with save/load, so simulation systems whose
+/// result depends on iteration order go through [`get_spawn_ordered`].
+#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub struct SpawnOrder(pub u64);
+
+/// Next [`SpawnOrder`] to hand out.
+#[derive(Resource, Default, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SpawnCounter(pub u64);
+
+/// Entity command giving a freshly spawned unit the next [`SpawnOrder`], e.g.
+/// `commands.spawn(bundle).add(assign_spawn_order)`. Commands are applied in
+/// schedule order, so the numbering doesn't depend on the frame rate.
+pub fn assign_spawn_order(mut entity: EntityWorldMut) {
+    let order = entity.world_scope(|world| {
+        let mut counter = world.get_resource_or_insert_with(SpawnCounter::default);
+        counter.0 += 1;
+        SpawnOrder(counter.0 - 1)
+    });
+    entity.insert(order);
+}
+
+/// Entities sorted by their [`SpawnOrder`]. Entities without one (spawned by
+/// hand in tests and tools) come last.
+pub fn get_spawn_ordered<'a>(
+    entities: impl Iterator<Item = (Entity, Option<&'a SpawnOrder>)>,
+) -> Vec<Entity> {
+    let mut entities: Vec<(u64, Entity)> = entities
+        .map(|(e, order)| (order.map_or(u64::MAX, |order| order.0), e))
+        .collect();
+    entities.sort_unstable();
+    entities.into_iter().map(|(_, e)| e).collect()
+}
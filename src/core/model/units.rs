@@ -0,0 +1,503 @@
+use std::{collections::HashMap, fmt};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use super::{
+    BeeMaterialUniform, BeeType, CurrencyGainPerMinute, CurrencyValues, EnemyType, LivingCreature,
+    RigidBody, TargetingStrategy, UniversalBehaviour, UniversalMaterial, BEE_ATLAS_HANDLE,
+    BIRB_ATLAS_HANDLE, BUMBLE_ATLAS_HANDLE, MAX_DEFENDER_LEVEL, MAX_WORKER_LEVEL,
+    WASP_ATLAS_HANDLE,
+};
+
+// Used when no asset was loaded, e.g. headless
+const DEFAULT_UNITS: &[u8] = include_bytes!("../../../assets/units/default.units.json");
+pub const DEFAULT_UNITS_PATH: &str = "units/default.units.json";
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitId {
+    Baby,
+    Regular,
+    Worker,
+    Defender,
+    Queen,
+    Wasp,
+    Birb,
+    Bumble,
+}
+
+impl UnitId {
+    // Levels the game spawns on its own
+    pub fn get_required_levels(&self) -> u32 {
+        match self {
+            UnitId::Worker => MAX_WORKER_LEVEL,
+            UnitId::Defender => MAX_DEFENDER_LEVEL,
+            // Endless waves go up to these
+            UnitId::Wasp => 2,
+            UnitId::Birb | UnitId::Bumble => 3,
+            UnitId::Baby | UnitId::Regular | UnitId::Queen => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct UnitKey {
+    pub id: UnitId,
+    pub level: u32,
+}
+
+impl From<BeeType> for UnitKey {
+    fn from(value: BeeType) -> Self {
+        let (id, level) = match value {
+            BeeType::Baby => (UnitId::Baby, 0),
+            BeeType::Regular => (UnitId::Regular, 0),
+            BeeType::Worker(lvl) => (UnitId::Worker, lvl),
+            BeeType::Defender(lvl) => (UnitId::Defender, lvl),
+            BeeType::Queen => (UnitId::Queen, 0),
+        };
+        UnitKey { id, level }
+    }
+}
+
+impl From<EnemyType> for UnitKey {
+    fn from(value: EnemyType) -> Self {
+        let (id, level) = match value {
+            EnemyType::Wasp(lvl) => (UnitId::Wasp, lvl),
+            EnemyType::Birb(lvl) => (UnitId::Birb, lvl),
+            EnemyType::Bumble(lvl) => (UnitId::Bumble, lvl),
+        };
+        UnitKey { id, level }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum UnitAtlas {
+    Bee,
+    Wasp,
+    Birb,
+    Bumble,
+}
+
+impl UnitAtlas {
+    pub fn get_handle(&self) -> Handle<Image> {
+        match self {
+            UnitAtlas::Bee => BEE_ATLAS_HANDLE,
+            UnitAtlas::Wasp => WASP_ATLAS_HANDLE,
+            UnitAtlas::Birb => BIRB_ATLAS_HANDLE,
+            UnitAtlas::Bumble => BUMBLE_ATLAS_HANDLE,
+        }
+    }
+}
+
+fn default_attack_radius() -> f32 {
+    14.0
+}
+
+fn default_multiplier() -> f32 {
+    1.0
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct UnitStats {
+    pub health: i32,
+    pub attack_damage: u32,
+    #[serde(default = "default_attack_radius")]
+    pub attack_radius: f32,
+    pub attack_cooldown: f32,
+    #[serde(default)]
+    pub end_game_on_dead: bool,
+
+    pub radius: f32,
+    pub max_valocity: f32,
+    pub max_acceleartion: f32,
+    pub separation_distance: f32,
+    pub separation_strength: f32,
+
+    pub max_wonder_distance_to_hive: f32,
+    pub min_wonder_distance_to_hive: f32,
+    pub min_wonder_distance: f32,
+    pub enemy_attack_distance_to_hive: f32,
+    pub enemy_attack_radius: f32,
+    pub enemy_attack_radius_if_alerted: f32,
+    pub alert_distance: f32,
+    pub flee_on_alarm: bool,
+    pub run_away_radius: f32,
+    pub min_healthpoints_before_run_away: i32,
+    #[serde(default)]
+    pub targeting: TargetingStrategy,
+
+    // Currency per minute
+    #[serde(default)]
+    pub gain: CurrencyValues,
+    // Set by the wave
+    #[serde(default = "default_multiplier")]
+    pub drop_multiplier: f32,
+
+    pub atlas: UnitAtlas,
+    pub tiles: UVec2,
+    pub shape: u32,
+    pub wing_states: u32,
+    pub wing_shape: u32,
+    pub overlay: UVec2,
+}
+
+impl UnitStats {
+    pub fn grow(&self, growth: &UnitGrowth, levels: u32) -> UnitStats {
+        let levels = levels.min(i32::MAX as u32) as i32;
+        let factor = |per_level: f32| per_level.powi(levels);
+        let health = factor(growth.health);
+        let speed = factor(growth.speed);
+        UnitStats {
+            health: (self.health as f32 * health).round() as i32,
+            min_healthpoints_before_run_away: (self.min_healthpoints_before_run_away as f32
+                * health)
+                .round() as i32,
+            attack_damage: (self.attack_damage as f32 * factor(growth.attack_damage)).round()
+                as u32,
+            attack_cooldown: self.attack_cooldown * factor(growth.attack_cooldown),
+            max_valocity: self.max_valocity * speed,
+            max_acceleartion: self.max_acceleartion * speed,
+            drop_multiplier: self.drop_multiplier * factor(growth.drop),
+            ..self.clone()
+        }
+    }
+
+    pub fn get_drop(&self, drop: CurrencyValues) -> CurrencyValues {
+        drop.map(|value| (value as f32 * self.drop_multiplier).round() as u64)
+    }
+
+    pub fn get_creature(&self) -> LivingCreature {
+        LivingCreature {
+            health: self.health,
+            max_health: self.health,
+            attack_damage: self.attack_damage,
+            attack_radius: self.attack_radius,
+            attack_cooldown: self.attack_cooldown,
+            end_game_on_dead: self.end_game_on_dead,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_rigid_body(&self) -> RigidBody {
+        RigidBody {
+            radius: self.radius,
+            max_valocity: self.max_valocity,
+            max_acceleartion: self.max_acceleartion,
+            separation_distance: self.separation_distance,
+            separation_strength: self.separation_strength,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_behaviour(&self) -> UniversalBehaviour {
+        UniversalBehaviour {
+            max_wonder_distance_to_hive: self.max_wonder_distance_to_hive,
+            min_wonder_distance_to_hive: self.min_wonder_distance_to_hive,
+            min_wonder_distance: self.min_wonder_distance,
+            enemy_attack_distance_to_hive: self.enemy_attack_distance_to_hive,
+            enemy_attack_radius: self.enemy_attack_radius,
+            enemy_attack_radius_if_alerted: self.enemy_attack_radius_if_alerted,
+            alert_distance: self.alert_distance,
+            flee_on_alarm: self.flee_on_alarm,
+            run_away_radius: self.run_away_radius,
+            min_healthpoints_before_run_away: self.min_healthpoints_before_run_away,
+            targeting: self.targeting,
+            time_since_last_refresh: 0.0,
+            retreating: false,
+            heal_progress: 0.0,
+        }
+    }
+
+    pub fn get_gain(&self) -> CurrencyGainPerMinute {
+        CurrencyGainPerMinute {
+            gain: self.gain,
+            ..Default::default()
+        }
+    }
+
+    // Wings at phase 0, see get_animation_phase
+    pub fn get_material(&self) -> UniversalMaterial {
+        UniversalMaterial {
+            props: BeeMaterialUniform {
+                color: Color::WHITE,
+                tiles_x: self.tiles.x,
+                tiles_y: self.tiles.y,
+                shape: self.shape,
+                wing_states: self.wing_states,
+                wing_shape: self.wing_shape,
+                overlay_x: self.overlay.x,
+                overlay_y: self.overlay.y,
+                phase: 0.0,
+                damage_time: -1.0,
+                upgrade_time: -1.0,
+                shield: 0.0,
+            },
+            texture: Some(self.atlas.get_handle()),
+        }
+    }
+}
+
+// From the entity, materials are made outside the simulation and must not use its rng
+pub fn get_animation_phase(entity: Entity) -> f32 {
+    let hash = entity.index().wrapping_mul(0x9E37_79B9) >> 16;
+    hash as f32 / 65536.0 * 16.0
+}
+
+// Applied once per level past the last defined one
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnitGrowth {
+    pub health: f32,
+    pub attack_damage: f32,
+    pub attack_cooldown: f32,
+    pub speed: f32,
+    pub drop: f32,
+}
+
+impl Default for UnitGrowth {
+    fn default() -> Self {
+        Self {
+            health: 1.0,
+            attack_damage: 1.0,
+            attack_cooldown: 1.0,
+            speed: 1.0,
+            drop: 1.0,
+        }
+    }
+}
+
+// Per level only the stats that differ
+#[derive(Deserialize)]
+struct RawUnit {
+    base: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    levels: Vec<serde_json::Map<String, serde_json::Value>>,
+    #[serde(default)]
+    growth: UnitGrowth,
+}
+
+#[derive(Clone, Debug)]
+pub struct UnitLevels {
+    pub levels: Vec<UnitStats>,
+    pub growth: UnitGrowth,
+}
+
+#[derive(Debug)]
+pub enum UnitDefinitionsLoaderError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    MissingUnit(UnitId),
+    MissingLevels {
+        unit: UnitId,
+        found: u32,
+        required: u32,
+    },
+    InvalidLevel {
+        unit: UnitId,
+        level: u32,
+        reason: String,
+    },
+    InvalidGrowth {
+        unit: UnitId,
+        reason: String,
+    },
+}
+
+impl fmt::Display for UnitDefinitionsLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitDefinitionsLoaderError::Io(e) => write!(f, "could not read units: {}", e),
+            UnitDefinitionsLoaderError::Json(e) => write!(f, "malformed units: {}", e),
+            UnitDefinitionsLoaderError::MissingUnit(unit) => {
+                write!(f, "{:?} is not defined", unit)
+            }
+            UnitDefinitionsLoaderError::MissingLevels {
+                unit,
+                found,
+                required,
+            } => write!(
+                f,
+                "{:?} has {} levels, at least {} are needed",
+                unit, found, required
+            ),
+            UnitDefinitionsLoaderError::InvalidLevel {
+                unit,
+                level,
+                reason,
+            } => write!(f, "{:?} level {} is invalid: {}", unit, level, reason),
+            UnitDefinitionsLoaderError::InvalidGrowth { unit, reason } => {
+                write!(f, "{:?} growth is invalid: {}", unit, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for UnitDefinitionsLoaderError {}
+
+impl From<std::io::Error> for UnitDefinitionsLoaderError {
+    fn from(value: std::io::Error) -> Self {
+        UnitDefinitionsLoaderError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for UnitDefinitionsLoaderError {
+    fn from(value: serde_json::Error) -> Self {
+        UnitDefinitionsLoaderError::Json(value)
+    }
+}
+
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct UnitDefinitions {
+    pub units: HashMap<UnitId, UnitLevels>,
+}
+
+impl UnitDefinitions {
+    pub fn from_slice(bytes: &[u8]) -> Result<UnitDefinitions, UnitDefinitionsLoaderError> {
+        let mut raw: HashMap<UnitId, RawUnit> = serde_json::from_slice(bytes)?;
+
+        let mut units = HashMap::new();
+        for unit in UnitId::iter() {
+            let Some(mut raw_unit) = raw.remove(&unit) else {
+                return Err(UnitDefinitionsLoaderError::MissingUnit(unit));
+            };
+            if raw_unit.levels.is_empty() {
+                raw_unit.levels.push(Default::default());
+            }
+
+            let required = unit.get_required_levels();
+            if (raw_unit.levels.len() as u32) < required {
+                return Err(UnitDefinitionsLoaderError::MissingLevels {
+                    unit,
+                    found: raw_unit.levels.len() as u32,
+                    required,
+                });
+            }
+
+            let growth = raw_unit.growth;
+            let factors = [
+                growth.health,
+                growth.attack_damage,
+                growth.attack_cooldown,
+                growth.speed,
+                growth.drop,
+            ];
+            if factors.iter().any(|f| !f.is_finite() || *f <= 0.0) {
+                return Err(UnitDefinitionsLoaderError::InvalidGrowth {
+                    unit,
+                    reason: format!("factors must be positive, got {:?}", growth),
+                });
+            }
+
+            let mut levels = Vec::with_capacity(raw_unit.levels.len());
+            for (level, overrides) in raw_unit.levels.into_iter().enumerate() {
+                let mut stats = raw_unit.base.clone();
+                stats.extend(overrides);
+                let stats: UnitStats = serde_json::from_value(stats.into()).map_err(|e| {
+                    UnitDefinitionsLoaderError::InvalidLevel {
+                        unit,
+                        level: level as u32,
+                        reason: e.to_string(),
+                    }
+                })?;
+                levels.push(stats);
+            }
+            units.insert(unit, UnitLevels { levels, growth });
+        }
+
+        Ok(UnitDefinitions { units })
+    }
+
+    pub fn get(&self, key: impl Into<UnitKey>) -> UnitStats {
+        let key = key.into();
+        let unit = &self.units[&key.id];
+        let last = unit.levels.len() as u32 - 1;
+        if key.level <= last {
+            unit.levels[key.level as usize].clone()
+        } else {
+            unit.levels[last as usize].grow(&unit.growth, key.level - last)
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct UnitDefinitionsLoader;
+
+impl AssetLoader for UnitDefinitionsLoader {
+    type Asset = UnitDefinitions;
+    type Settings = ();
+    type Error = UnitDefinitionsLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<UnitDefinitions, UnitDefinitionsLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            UnitDefinitions::from_slice(&bytes)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["units.json"]
+    }
+}
+
+// Built-in definitions until the asset is loaded
+#[derive(Resource, Clone, Debug)]
+pub struct UnitRegistry(pub UnitDefinitions);
+
+impl Default for UnitRegistry {
+    fn default() -> Self {
+        UnitRegistry(
+            UnitDefinitions::from_slice(DEFAULT_UNITS).expect("built-in units must be valid"),
+        )
+    }
+}
+
+impl UnitRegistry {
+    pub fn get(&self, key: impl Into<UnitKey>) -> UnitStats {
+        self.0.get(key)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct UnitDefinitionsHandle(pub Handle<UnitDefinitions>);
+
+pub fn load_unit_definitions_system(
+    mut handle: ResMut<UnitDefinitionsHandle>,
+    asset_server: Res<AssetServer>,
+) {
+    handle.0 = asset_server.load(DEFAULT_UNITS_PATH);
+}
+
+// Existing units keep their stats
+pub fn apply_unit_definitions_system(
+    mut events: EventReader<AssetEvent<UnitDefinitions>>,
+    handle: Res<UnitDefinitionsHandle>,
+    definitions: Res<Assets<UnitDefinitions>>,
+    mut registry: ResMut<UnitRegistry>,
+) {
+    for event in events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }
+                if *id == handle.0.id() =>
+            {
+                if let Some(units) = definitions.get(*id) {
+                    registry.0 = units.clone();
+                    info!("Unit definitions loaded");
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,28 +1,67 @@
-use bevy::prelude::*;
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, AsyncComputeTaskPool, Task},
+};
 use delaunator::{triangulate, Point};
-use rand::{rngs::StdRng, SeedableRng};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-use super::{hive_map::*, precomputed::*};
+use super::hive_map::*;
 
 pub const HIVE_GRAPH_POINTS_NUMBER: usize = 512;
-pub const HIVE_GRAPH_RADIUS: f32 = 180.0;
-pub const HIVE_GRAPH_INNER_RADIUS: f32 = 160.0;
+// 180 on the original 320 wide hive
+pub const HIVE_GRAPH_RADIUS_FACTOR: f32 = 180.0 / 320.0;
+
+// Bump when the cached data or the way it is built changes
+pub const HIVE_GRAPH_CACHE_VERSION: u32 = 1;
+pub const HIVE_GRAPH_CACHE_DIR: &str = "cache/navigation";
+
+const UNREACHABLE: f32 = 1e9;
 
 #[derive(Resource, Default)]
 pub struct HiveGraph {
     pub ready: bool,
+    // HiveMap::get_hash of the map the graph was built for
+    pub map_hash: u64,
+    pub radius: f32,
     pub points: Vec<Vec2>,
     pub adjacent_points: Vec<Vec<usize>>,
     pub next_points: Vec<Vec<Vec<(usize, f32)>>>,
     pub hull: Vec<usize>,
-    pub rng: Option<StdRng>,
     pub distances: Vec<Vec<f32>>,
 }
 
-#[derive(Clone)]
-pub struct CrazyWrapper(*mut Vec<Vec<f32>>);
+// Where built graphs are cached, None always builds from scratch
+#[derive(Resource)]
+pub struct HiveGraphCache(pub Option<PathBuf>);
 
-unsafe impl Send for CrazyWrapper {}
+impl Default for HiveGraphCache {
+    fn default() -> Self {
+        HiveGraphCache(Some(PathBuf::from(HIVE_GRAPH_CACHE_DIR)))
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct HiveGraphTask(Option<Task<HiveGraph>>);
+
+// Next points are cheap to derive and big on disk, so they aren't cached
+#[derive(Serialize, Deserialize)]
+struct CachedHiveGraph {
+    // Must stay the first field, it is read on its own
+    version: u32,
+    map_hash: u64,
+    points: Vec<Vec2>,
+    adjacent_points: Vec<Vec<usize>>,
+    hull: Vec<usize>,
+    distances: Vec<Vec<f32>>,
+}
 
 impl HiveGraph {
     pub fn get_next(&self, from: usize, to: usize, seed: usize) -> usize {
@@ -39,55 +78,125 @@ impl HiveGraph {
 
     pub fn get_nearest(&self, p: Vec2) -> usize {
         let mut nearest = 0;
-        for i in 1..HIVE_GRAPH_POINTS_NUMBER {
+        for i in 1..self.points.len() {
             if self.points[i].distance(p) < self.points[nearest].distance(p) {
                 nearest = i;
             }
         }
         nearest
     }
-}
 
-pub fn build_hive_graph_system(
-    hive_map: Res<HiveMap>,
-    mut hive_graph: ResMut<HiveGraph>,
-    mut gizmos: Gizmos,
-    keyboard: Res<Input<KeyCode>>,
-) {
-    if hive_graph.ready && keyboard.pressed(KeyCode::Z) {
-        for p in &hive_graph.points {
-            gizmos.circle_2d(*p, 2.0, Color::GREEN);
+    // Slow, build_hive_graph_system runs it off the main thread
+    pub fn build(map: &HiveMap) -> Self {
+        let points = sample_points(map);
+        let (adjacent_points, hull) = connect_points(map, &points);
+        let distances = compute_distances(&points, &adjacent_points);
+        Self::from_parts(map, points, adjacent_points, hull, distances)
+    }
+
+    fn from_parts(
+        map: &HiveMap,
+        points: Vec<Vec2>,
+        adjacent_points: Vec<Vec<usize>>,
+        hull: Vec<usize>,
+        distances: Vec<Vec<f32>>,
+    ) -> Self {
+        let next_points = compute_next_points(&adjacent_points, &distances);
+        HiveGraph {
+            ready: true,
+            map_hash: map.get_hash(),
+            radius: get_graph_radius(map),
+            points,
+            adjacent_points,
+            next_points,
+            hull,
+            distances,
         }
+    }
 
-        for i in 0..HIVE_GRAPH_POINTS_NUMBER {
-            for j in hive_graph.adjacent_points[i].iter() {
-                gizmos.line_2d(hive_graph.points[i], hive_graph.points[*j], Color::BLUE);
-            }
+    fn get_cache_path(dir: &Path, map_hash: u64) -> PathBuf {
+        dir.join(format!("{:016x}.bin", map_hash))
+    }
+
+    fn read_cache(dir: &Path, map: &HiveMap) -> Option<Self> {
+        let map_hash = map.get_hash();
+        let bytes = fs::read(Self::get_cache_path(dir, map_hash)).ok()?;
+        let version: u32 = bincode::deserialize(&bytes).ok()?;
+        if version != HIVE_GRAPH_CACHE_VERSION {
+            return None;
         }
+        let cached: CachedHiveGraph = bincode::deserialize(&bytes).ok()?;
+        if cached.map_hash != map_hash {
+            return None;
+        }
+        Some(Self::from_parts(
+            map,
+            cached.points,
+            cached.adjacent_points,
+            cached.hull,
+            cached.distances,
+        ))
     }
-    if hive_graph.ready || !hive_map.ready {
-        return;
+
+    fn write_cache(&self, dir: &Path) -> io::Result<()> {
+        let cached = CachedHiveGraph {
+            version: HIVE_GRAPH_CACHE_VERSION,
+            map_hash: self.map_hash,
+            points: self.points.clone(),
+            adjacent_points: self.adjacent_points.clone(),
+            hull: self.hull.clone(),
+            distances: self.distances.clone(),
+        };
+        let bytes = bincode::serialize(&cached).map_err(io::Error::other)?;
+        fs::create_dir_all(dir)?;
+        fs::write(Self::get_cache_path(dir, self.map_hash), bytes)
     }
 
-    hive_graph.ready = true;
+    pub fn load_or_build(map: &HiveMap, cache_dir: Option<&Path>) -> Self {
+        if let Some(graph) = cache_dir.and_then(|dir| Self::read_cache(dir, map)) {
+            return graph;
+        }
 
-    /*let mut rng = StdRng::seed_from_u64(1);
+        let graph = Self::build(map);
+        if let Some(dir) = cache_dir {
+            if let Err(e) = graph.write_cache(dir) {
+                warn!("Failed to cache the hive graph in {}: {}", dir.display(), e);
+            }
+        }
+        graph
+    }
+}
+
+fn get_graph_radius(map: &HiveMap) -> f32 {
+    map.get_world_size().max_element() * HIVE_GRAPH_RADIUS_FACTOR
+}
+
+// Each point as far from the others as a few random candidates allows
+fn sample_points(map: &HiveMap) -> Vec<Vec2> {
+    let radius = get_graph_radius(map);
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut points: Vec<Vec2> = vec![];
+
+    // Mostly obstructed maps may not fit them all
+    for _ in 0..HIVE_GRAPH_POINTS_NUMBER * 4 {
+        if points.len() >= HIVE_GRAPH_POINTS_NUMBER {
+            break;
+        }
 
-    while hive_graph.points.len() < HIVE_GRAPH_POINTS_NUMBER {
         let mut furthest_point = Vec2::ZERO;
         let mut furthest_point_dist = 0.0;
 
         for _ in 0..48 {
-            let x = rng.gen_range(-HIVE_GRAPH_RADIUS..HIVE_GRAPH_RADIUS);
-            let y = rng.gen_range(-HIVE_GRAPH_RADIUS..HIVE_GRAPH_RADIUS);
-            if x * x + y * y >= HIVE_GRAPH_RADIUS.powi(2) {
+            let x = rng.gen_range(-radius..radius);
+            let y = rng.gen_range(-radius..radius);
+            if x * x + y * y >= radius.powi(2) {
                 continue;
             }
-            if hive_map.get_obstruction_xy(x, y) > 0.0 {
+            if map.get_obstruction_xy(x, y) > 0.0 {
                 continue;
             }
             let mut closest_dist = 1e9f32;
-            for other_point in &hive_graph.points {
+            for other_point in &points {
                 closest_dist = closest_dist.min(Vec2::new(x, y).distance(*other_point));
             }
 
@@ -98,24 +207,20 @@ pub fn build_hive_graph_system(
         }
 
         if furthest_point_dist > 0.0 {
-            hive_graph.points.push(furthest_point);
+            points.push(furthest_point);
         }
-    }*/
-
-    hive_graph.points = POINTS.iter().map(|(x, y)| Vec2::new(*x, *y)).collect();
+    }
 
-    hive_graph.adjacent_points.reserve(HIVE_GRAPH_POINTS_NUMBER);
+    points
+}
 
-    for _i in 0..HIVE_GRAPH_POINTS_NUMBER {
-        let adjacent: Vec<usize> = vec![];
-        hive_graph.adjacent_points.push(adjacent);
-    }
+// Delaunay edges that don't cross obstacles
+fn connect_points(map: &HiveMap, points: &[Vec2]) -> (Vec<Vec<usize>>, Vec<usize>) {
+    let mut adjacent_points = vec![vec![]; points.len()];
 
     let res = triangulate(
-        &hive_graph
-            .points
-            .clone()
-            .drain(..)
+        &points
+            .iter()
             .map(|v| Point {
                 x: v.x as f64,
                 y: v.y as f64,
@@ -123,13 +228,12 @@ pub fn build_hive_graph_system(
             .collect::<Vec<_>>()[..],
     );
 
-    let points = hive_graph.points.clone();
     let any_obstacles = |i: usize, j: usize| -> bool {
         let u = points[i];
         let v = points[j];
         for f in [0.1, 0.2, 0.5, 0.8, 0.9] {
             let o = u * f + v * (1.0 - f);
-            if hive_map.get_obstruction(o) > 0.1 {
+            if map.get_obstruction(o) > 0.1 {
                 return true;
             }
         }
@@ -141,13 +245,13 @@ pub fn build_hive_graph_system(
         let b = res.triangles[i * 3 + 1];
         let c = res.triangles[i * 3 + 2];
         if !any_obstacles(a, b) {
-            hive_graph.adjacent_points[a].push(b);
+            adjacent_points[a].push(b);
         }
         if !any_obstacles(b, c) {
-            hive_graph.adjacent_points[b].push(c);
+            adjacent_points[b].push(c);
         }
         if !any_obstacles(c, a) {
-            hive_graph.adjacent_points[c].push(a);
+            adjacent_points[c].push(a);
         }
     }
 
@@ -155,95 +259,52 @@ pub fn build_hive_graph_system(
         let a = res.hull[i];
         let b = res.hull[if i == 0 { res.hull.len() - 1 } else { i - 1 }];
         if !any_obstacles(a, b) {
-            hive_graph.adjacent_points[a].push(b);
+            adjacent_points[a].push(b);
         }
     }
 
-    let distance = &DISTANCES;
+    (adjacent_points, res.hull)
+}
 
-    /*let mut distance = vec![];
-    for _ in 0..HIVE_GRAPH_POINTS_NUMBER {
-        distance.push(vec![1e9; HIVE_GRAPH_POINTS_NUMBER]);
-    }
+// One Dijkstra per point, far cheaper than Floyd-Warshall on a triangulation
+fn compute_distances(points: &[Vec2], adjacent_points: &[Vec<usize>]) -> Vec<Vec<f32>> {
+    let mut distances = vec![vec![UNREACHABLE; points.len()]; points.len()];
+    let mut queue = BinaryHeap::new();
 
-    for i in 0..HIVE_GRAPH_POINTS_NUMBER {
-        distance[i][i] = 0.0;
-        for j in hive_graph.adjacent_points[i].iter() {
-            distance[i][*j] = points[i].distance(points[*j]);
-        }
-    }*/
-
-    //let distance_ptr: *mut Vec<Vec<f32>> = &mut distance;
-    /*for k in 0..HIVE_GRAPH_POINTS_NUMBER {
-        /*
-        bevy::tasks::ComputeTaskPool::get().scope(|scope| {
-            for f in 0..16 {
-                let left = HIVE_GRAPH_POINTS_NUMBER / 16 * f;
-                let right = HIVE_GRAPH_POINTS_NUMBER / 16 * (f + 1);
-
-                let distance_ptr_clone = CrazyWrapper(distance_ptr.clone());
-
-                scope.spawn(async move {
-                    let v = distance_ptr_clone.clone();
-                    unsafe {
-                        let distance = &*(v.0);
-                        for i in left..right {
-                            for j in 0..HIVE_GRAPH_POINTS_NUMBER {
-                                if distance[i][j] > distance[i][k] + distance[k][j] {
-                                }
-                            }
-                        }
-                    }
-                });
-            }
-        });*/
+    for (from, distance) in distances.iter_mut().enumerate() {
+        distance[from] = 0.0;
+        // Bits of non-negative floats sort like the floats themselves
+        queue.push(Reverse((0f32.to_bits(), from)));
 
-        for i in 0..HIVE_GRAPH_POINTS_NUMBER {
-            for j in 0..HIVE_GRAPH_POINTS_NUMBER {
-                if distance[i][j] > distance[i][k] + distance[k][j] {
-                    distance[i][j] = distance[i][k] + distance[k][j];
+        while let Some(Reverse((bits, i))) = queue.pop() {
+            let dist = f32::from_bits(bits);
+            if dist > distance[i] {
+                continue;
+            }
+            for j in adjacent_points[i].iter() {
+                let next = dist + points[i].distance(points[*j]);
+                if next < distance[*j] {
+                    distance[*j] = next;
+                    queue.push(Reverse((next.to_bits(), *j)));
                 }
             }
         }
-    }*/
-
-    /*let mut file: std::fs::File = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .open("my-file")
-        .unwrap();
-
-    for i in 0..HIVE_GRAPH_POINTS_NUMBER {
-        write!(file, "vec![");
-        for j in 0..HIVE_GRAPH_POINTS_NUMBER {
-            write!(file, "{},", distance[i][j]);
-        }
-        writeln!(file, "],");
-    }*/
-
-    /*for k in 0..HIVE_GRAPH_POINTS_NUMBER {
     }
-    for i in 0..HIVE_GRAPH_POINTS_NUMBER {
-        for j in 0..HIVE_GRAPH_POINTS_NUMBER {
-            let mut nearest_point = hive_graph.adjacent_points[i][0];
 
-            for k in hive_graph.adjacent_points[i].iter() {
-                if distance[i][*k] + distance[*k][j] < distance[i][nearest_point] + distance[nearest_point][j]
-                {
-                    nearest_point = *k;
-                }
-            }
+    distances
+}
 
-            let diff = (distance[i][nearest_point] + distance[nearest_point][j]) - distance[i][j];
-            if diff.abs() > 0.001 {
-                println!("Omg diff: {}-{}-{}    {}", i, nearest_point, j, diff);
-            }
-        }
-    }*/
+// Best and second best step from every point towards every other one
+fn compute_next_points(
+    adjacent_points: &[Vec<usize>],
+    distance: &[Vec<f32>],
+) -> Vec<Vec<Vec<(usize, f32)>>> {
+    let points_number = adjacent_points.len();
+    let mut all_next_points = Vec::with_capacity(points_number);
 
-    for i in 0..HIVE_GRAPH_POINTS_NUMBER {
+    for i in 0..points_number {
         let mut next_points = vec![];
-        for f in 0..HIVE_GRAPH_POINTS_NUMBER {
+        for f in 0..points_number {
             let mut next_points_local = vec![];
 
             if i == f {
@@ -251,21 +312,19 @@ pub fn build_hive_graph_system(
                 continue;
             }
 
-            if hive_graph.adjacent_points[i].len() == 1 {
-                next_points_local.push((hive_graph.adjacent_points[i][0], 1.0));
-            } else if hive_graph.adjacent_points[i].len() > 1 {
-                let mut nearest_point = hive_graph.adjacent_points[i][0];
-                let mut nearest_point_next = hive_graph.adjacent_points[i][1];
+            if adjacent_points[i].len() == 1 {
+                next_points_local.push((adjacent_points[i][0], 1.0));
+            } else if adjacent_points[i].len() > 1 {
+                let mut nearest_point = adjacent_points[i][0];
+                let mut nearest_point_next = adjacent_points[i][1];
 
                 if distance[i][nearest_point] + distance[nearest_point][f]
                     > distance[i][nearest_point_next] + distance[nearest_point_next][f]
                 {
-                    let tmp = nearest_point;
-                    nearest_point = nearest_point_next;
-                    nearest_point_next = tmp;
+                    std::mem::swap(&mut nearest_point, &mut nearest_point_next);
                 }
 
-                for j in hive_graph.adjacent_points[i].iter() {
+                for j in adjacent_points[i].iter() {
                     if distance[i][*j] + distance[*j][f]
                         < distance[i][nearest_point] + distance[nearest_point][f]
                     {
@@ -276,25 +335,68 @@ pub fn build_hive_graph_system(
                     {
                         nearest_point_next = *j;
                     }
-                    /*if distance[i][*j] + distance[*j][f] < distance[i][nearest_point] + distance[nearest_point][f]
-                    {
-                        nearest_point = *j;
-                    }*/
                 }
 
-                //next_points_local.push((nearest_point, 1.0));
                 next_points_local.push((nearest_point, 0.7));
                 next_points_local.push((nearest_point_next, 0.3));
             }
 
             next_points.push(next_points_local);
         }
-        hive_graph.next_points.push(next_points);
+        all_next_points.push(next_points);
+    }
+
+    all_next_points
+}
+
+pub fn draw_hive_graph_system(
+    hive_graph: Res<HiveGraph>,
+    mut gizmos: Gizmos,
+    keyboard: Res<Input<KeyCode>>,
+) {
+    if hive_graph.ready && keyboard.pressed(KeyCode::Z) {
+        for p in &hive_graph.points {
+            gizmos.circle_2d(*p, 2.0, Color::GREEN);
+        }
+
+        for i in 0..hive_graph.points.len() {
+            for j in hive_graph.adjacent_points[i].iter() {
+                gizmos.line_2d(hive_graph.points[i], hive_graph.points[*j], Color::BLUE);
+            }
+        }
     }
+}
 
-    hive_graph.hull = res.hull;
+// Rebuilt on the async pool whenever the map changes, the simulation waits for it
+pub fn build_hive_graph_system(
+    hive_map: Res<HiveMap>,
+    mut hive_graph: ResMut<HiveGraph>,
+    mut task: ResMut<HiveGraphTask>,
+    cache: Res<HiveGraphCache>,
+) {
+    if hive_map.is_changed() && !hive_map.ready {
+        // The map is being replaced, a graph for the old one is of no use
+        hive_graph.ready = false;
+        task.0 = None;
+    }
 
-    hive_graph.rng = Some(StdRng::seed_from_u64(0));
+    if hive_map.is_changed() && hive_map.ready {
+        let map_hash = hive_map.get_hash();
+        if !hive_graph.ready || hive_graph.map_hash != map_hash {
+            hive_graph.ready = false;
+            let map = hive_map.clone();
+            let cache_dir = cache.0.clone();
+            task.0 = Some(AsyncComputeTaskPool::get().spawn(async move {
+                HiveGraph::load_or_build(&map, cache_dir.as_deref())
+            }));
+        }
+    }
+
+    if task.0.as_ref().is_some_and(|task| task.is_finished()) {
+        *hive_graph = block_on(task.0.take().unwrap());
+    }
+}
 
-    //hive_graph.distances = distance;
+pub fn navigation_ready(hive_graph: Option<Res<HiveGraph>>) -> bool {
+    hive_graph.is_none_or(|hive_graph| hive_graph.ready)
 }
@@ -1,78 +1,139 @@
 use bevy::prelude::*;
 
-use crate::core::{HIVE_IMAGE_SIZE, HIVE_WORLD_SIZE};
+use crate::{
+    core::{HiveBuildings, HiveDefinition},
+    utils::StableHasher,
+};
 
-const HIVE_MAP_SIZE: usize = 160;
-
-const ALLOWED_COLORS: [(u8, u8, u8, u8); 3] =
-    [(246, 180, 0, 255), (246, 188, 0, 255), (255, 255, 255, 0)];
-
-#[derive(Resource, Default)]
+/// Walls of the hive, one value per pixel of its image.
+#[derive(Resource, Default, Clone)]
 pub struct HiveMap {
     pub ready: bool,
+    /// Hive the map was built for.
+    pub hive: HiveDefinition,
+    pub width: usize,
+    pub height: usize,
     pub data: Vec<u32>,
 }
 
 impl HiveMap {
+    /// Map without any walls, sized like the image of `hive`.
+    pub fn open(hive: &HiveDefinition) -> Self {
+        let width = hive.image_size.x as usize;
+        let height = hive.image_size.y as usize;
+        HiveMap {
+            ready: true,
+            hive: hive.clone(),
+            width,
+            height,
+            data: vec![0; width * height],
+        }
+    }
+
+    pub fn get_world_size(&self) -> Vec2 {
+        self.hive.world_size
+    }
+
     pub fn get_obstruction_xy(&self, x: f32, y: f32) -> f32 {
         self.get_obstruction(Vec2::new(x, y))
     }
 
     pub fn get_obstruction(&self, pos: Vec2) -> f32 {
-        if !self.ready {}
-        if pos.x <= -HIVE_WORLD_SIZE / 2.0 || pos.x >= HIVE_WORLD_SIZE / 2.0 {
+        let world_size = self.get_world_size();
+        if pos.x <= -world_size.x / 2.0 || pos.x >= world_size.x / 2.0 {
             return 0.0;
         }
-        if pos.y <= -HIVE_WORLD_SIZE / 2.0 || pos.y >= HIVE_WORLD_SIZE / 2.0 {
+        if pos.y <= -world_size.y / 2.0 || pos.y >= world_size.y / 2.0 {
             return 0.0;
         }
-        let x = ((pos.x / HIVE_WORLD_SIZE + 0.5) * HIVE_MAP_SIZE as f32) as usize;
-        let y = ((-pos.y / HIVE_WORLD_SIZE + 0.5) * HIVE_MAP_SIZE as f32) as usize;
+        let x = ((pos.x / world_size.x + 0.5) * self.width as f32) as usize;
+        let y = ((-pos.y / world_size.y + 0.5) * self.height as f32) as usize;
+
+        self.data[x.min(self.width - 1) + y.min(self.height - 1) * self.width] as f32
+    }
+
+    /// Identifies the layout, things derived from the map are cached by it.
+    pub fn get_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        hasher.write_u64(self.width as u64);
+        hasher.write_u64(self.height as u64);
+        hasher.write_f32(self.hive.world_size.x);
+        hasher.write_f32(self.hive.world_size.y);
+        hasher.write_u64(self.data.len() as u64);
+        for value in self.data.iter() {
+            hasher.write_u32(*value);
+        }
+        hasher.finish()
+    }
+
+    /// Builds the map from the hive image, opaque pixels not in the hive's
+    /// palette block movement.
+    pub fn from_image(image: &Image, hive: &HiveDefinition) -> Self {
+        let width = image.width() as usize;
+        let height = image.height() as usize;
+        if image.size() != hive.image_size {
+            warn!(
+                "{} is {}x{}, but the hive expects {}x{}",
+                hive.image,
+                width,
+                height,
+                hive.image_size.x,
+                hive.image_size.y
+            );
+        }
+
+        let mut hive_map = HiveMap {
+            ready: true,
+            hive: hive.clone(),
+            width,
+            height,
+            data: vec![0; width * height],
+        };
 
-        self.data[x + y * HIVE_MAP_SIZE] as f32
-            / ((HIVE_IMAGE_SIZE * HIVE_IMAGE_SIZE) / (HIVE_MAP_SIZE * HIVE_MAP_SIZE)) as f32
+        for (i, value) in hive_map.data.iter_mut().enumerate() {
+            let idx = i * 4;
+            let color = [
+                image.data[idx],
+                image.data[idx + 1],
+                image.data[idx + 2],
+                image.data[idx + 3],
+            ];
+            if !hive.is_passable(color) {
+                *value = 1;
+            }
+        }
+
+        hive_map
     }
 }
 
+/// Loads the map from the image of the hive in play unless it was provided
+/// some other way. Without image assets (headless) the map has to be
+/// inserted up front.
 pub fn build_hive_map_system(
     mut hive_map: ResMut<HiveMap>,
-    asset_server: ResMut<AssetServer>,
-    images: Res<Assets<Image>>,
+    hive_buildings: Res<HiveBuildings>,
+    asset_server: Res<AssetServer>,
+    images: Option<Res<Assets<Image>>>,
     mut image_handle: Local<Handle<Image>>,
 ) {
-    if hive_map.ready {
+    let Some(images) = images else {
+        return;
+    };
+    let hive = &hive_buildings.hive;
+    if hive_map.ready && hive_map.hive == *hive {
         return;
     }
+    if hive_map.ready {
+        // Nothing can find its way until the new map is there
+        hive_map.ready = false;
+    }
 
-    if *image_handle == Handle::default() {
-        *image_handle = asset_server.load("images/Hive.png");
+    if image_handle.path().map(|path| path.to_string()) != Some(hive.image.clone()) {
+        *image_handle = asset_server.load(&hive.image);
     }
 
     if let Some(image) = images.get(image_handle.clone()) {
-        assert!(image.width() == HIVE_IMAGE_SIZE as u32);
-        assert!(image.height() == HIVE_IMAGE_SIZE as u32);
-
-        hive_map.ready = true;
-        hive_map.data.resize(HIVE_MAP_SIZE * HIVE_MAP_SIZE, 0);
-
-        let scale = HIVE_IMAGE_SIZE / HIVE_MAP_SIZE;
-        for i in 0..HIVE_MAP_SIZE {
-            for j in 0..HIVE_MAP_SIZE {
-                for dx in 0..scale {
-                    for dy in 0..scale {
-                        let idx = (i * scale + dx + (j * scale + dy) * HIVE_IMAGE_SIZE) * 4;
-                        let color = (
-                            image.data[idx],
-                            image.data[idx + 1],
-                            image.data[idx + 2],
-                            image.data[idx + 3],
-                        );
-                        if !ALLOWED_COLORS.contains(&color) && color.3 == 255 {
-                            hive_map.data[i + j * HIVE_MAP_SIZE] += 1;
-                        }
-                    }
-                }
-            }
-        }
+        *hive_map = HiveMap::from_image(image, hive);
     }
 }
@@ -1,9 +1,10 @@
 use bevy::prelude::*;
 
+use super::LogicSet;
+
 mod hive_graph;
 mod hive_map;
 mod navigation_target;
-mod precomputed;
 
 pub use hive_graph::*;
 pub use hive_map::*;
@@ -15,8 +16,15 @@ impl Plugin for NavigationPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.init_resource::<HiveMap>();
         app.init_resource::<HiveGraph>();
+        app.init_resource::<HiveGraphTask>();
+        app.init_resource::<HiveGraphCache>();
         app.add_systems(PreUpdate, build_hive_map_system);
-        app.add_systems(PreUpdate, build_hive_graph_system);
-        app.add_systems(Update, navigation_system);
+        app.add_systems(PreUpdate, build_hive_graph_system.after(build_hive_map_system));
+        app.add_systems(
+            PostUpdate,
+            (draw_hive_graph_system, draw_navigation_targets_system)
+                .run_if(resource_exists::<GizmoConfig>()),
+        );
+        app.add_systems(FixedUpdate, navigation_system.in_set(LogicSet::Navigation));
     }
 }
@@ -1,8 +1,9 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
+use rand::Rng;
 
-use crate::{utils::{dist_to_segment, FlatProvider}, core::GameInfo};
+use crate::{utils::{dist_to_segment, FlatProvider}, core::{get_spawn_ordered, GameRng, RngStream, SpawnOrder}};
 
-use super::{HiveGraph, HIVE_GRAPH_RADIUS};
+use super::HiveGraph;
 
 const REACH_DISTANCE: f32 = 4.0;
 
@@ -52,27 +53,31 @@ pub fn navigation_system(
         &mut NavigationResult,
     )>,
     all_entities: Query<&Transform>,
+    orders: Query<(Entity, Option<&SpawnOrder>), With<NavigationResult>>,
     graph: Res<HiveGraph>,
     time: Res<Time>,
-    game: Res<GameInfo>,
-    mut gizmos: Gizmos,
-    keyboard: Res<Input<KeyCode>>,
+    mut game_rng: ResMut<GameRng>,
 ) {
-    if game.paused {
-        return;
-    }
     if !graph.ready {
         return;
     }
 
-    let seed = (time.elapsed_seconds() * 100.0) as usize;
+    let radius = graph.radius;
+
+    // Drawn per unit, so units on the same spot don't all take the same way.
+    // Drawn up front in spawn order, the query order changes between runs.
+    let rng = game_rng.stream(RngStream::Navigation);
+    let seeds: HashMap<Entity, usize> = get_spawn_ordered(orders.iter())
+        .into_iter()
+        .map(|e| (e, rng.gen_range(0..1_000_000usize)))
+        .collect();
 
     // todo: parallel??
     query
         .iter_mut()
         .for_each(|(e, target, target_changed, transform, mut result)| {
             let from = transform.flat();
-            let seed = seed + e.index() as usize;
+            let seed = seeds.get(&e).copied().unwrap_or_default();
             if target_changed {
                 *result = NavigationResult::default();
             }
@@ -84,17 +89,14 @@ pub fn navigation_system(
                 }
                 NavigationTarget::Position(to) => 'position: {
                     let to = *to;
-                    if keyboard.pressed(KeyCode::X) {
-                        gizmos.line_2d(from, to, Color::PURPLE);
-                    }
 
                     if from.distance_squared(to) < REACH_DISTANCE.powi(2) || result.target_reached {
                         result.target_reached = true;
                         break 'position;
                     }
 
-                    if dist_to_segment(from, to, Vec2::ZERO) > HIVE_GRAPH_RADIUS * 0.9
-                        || from.length() > HIVE_GRAPH_RADIUS * 2.0
+                    if dist_to_segment(from, to, Vec2::ZERO) > radius * 0.9
+                        || from.length() > radius * 2.0
                     {
                         result.next_path_point = Some(to);
                         result.next_and_last = None;
@@ -133,9 +135,6 @@ pub fn navigation_system(
                 NavigationTarget::Entity(e, target_distance) => 'entity: {
                     if let Ok(to) = all_entities.get(*e) {
                         let to = to.flat();
-                        if keyboard.pressed(KeyCode::C) {
-                            gizmos.line_2d(from, to, Color::RED);
-                        }
 
                         let from_to_sqr = from.distance_squared(to);
 
@@ -162,8 +161,8 @@ pub fn navigation_system(
                                 break 'entity;
                             }
 
-                            if dist_to_segment(from, to, Vec2::ZERO) > HIVE_GRAPH_RADIUS * 0.85
-                                || from.length() > HIVE_GRAPH_RADIUS * 1.5
+                            if dist_to_segment(from, to, Vec2::ZERO) > radius * 0.85
+                                || from.length() > radius * 1.5
                             {
                                 result.next_path_point = Some(to);
                                 result.next_and_last = None;
@@ -208,3 +207,26 @@ pub fn navigation_system(
             }
         });
 }
+
+/// Hold `X` to see where units walk to, `C` to see whom they chase.
+pub fn draw_navigation_targets_system(
+    targets: Query<(&NavigationTarget, &Transform)>,
+    all_entities: Query<&Transform>,
+    mut gizmos: Gizmos,
+    keyboard: Res<Input<KeyCode>>,
+) {
+    for (target, transform) in targets.iter() {
+        let from = transform.flat();
+        match target {
+            NavigationTarget::Position(to) if keyboard.pressed(KeyCode::X) => {
+                gizmos.line_2d(from, *to, Color::PURPLE);
+            }
+            NavigationTarget::Entity(e, _) if keyboard.pressed(KeyCode::C) => {
+                if let Ok(to) = all_entities.get(*e) {
+                    gizmos.line_2d(from, to.flat(), Color::RED);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::core::{BeeType, HiveBuildings, LivingCreature, UniversalMaterial, FONT_HANDLE, BuildingKind, BUILDINGS_NUM, Building, MouseState, CurrencyGainPerMinute};
+use crate::core::{BeeType, HiveBuildings, LivingCreature, UniversalMaterial, FONT_HANDLE, BuildingKind, Building, MouseState, UnitRegistry};
 use bevy::{prelude::*, ui::FocusPolicy};
 use strum::IntoEnumIterator;
 
@@ -42,6 +42,7 @@ pub fn setup_bee_counters(
     builder: &mut ChildBuilder,
     mut materials: ResMut<Assets<UniversalMaterial>>,
     mut asset_server: &mut AssetServer,
+    units: &UnitRegistry,
 ) {
     builder.spawn(
         (NodeBundle {
@@ -183,7 +184,7 @@ pub fn setup_bee_counters(
                         BeeType::Worker(0),
                         BeeType::Defender(0),
                     ] {
-                        spawn_bee_counter(builder, kind, &mut asset_server, &mut materials, 12.0)
+                        spawn_bee_counter(builder, kind, &mut asset_server, &mut materials, units, 12.0)
                     }
                     // Padding
                     builder.spawn((
@@ -198,6 +199,7 @@ pub fn setup_bee_counters(
                         BeeType::Queen,
                         &mut asset_server,
                         &mut materials,
+                        units,
                         12.0,
                     )
                 });
@@ -209,10 +211,10 @@ fn spawn_bee_counter(
     kind: BeeType,
     _asset_server: &mut AssetServer,
     materials: &mut Assets<UniversalMaterial>,
+    units: &UnitRegistry,
     right: f32,
 ) {
-    let creature = LivingCreature::from(kind);
-    let gain = CurrencyGainPerMinute::from(kind).gain;
+    let stats = units.get(kind);
     builder
         .spawn((
             NodeBundle { 
@@ -225,11 +227,11 @@ fn spawn_bee_counter(
             },
             StatsTarget {
                 bee: Some(kind),
-                attack: creature.attack_damage,
-                cooldown: creature.attack_cooldown,
-                health: creature.health as u32,
-                honey: gain[0] as u32,
-                wax: gain[1] as u32,
+                attack: stats.attack_damage,
+                cooldown: stats.attack_cooldown,
+                health: stats.health as u32,
+                honey: stats.gain[0] as u32,
+                wax: stats.gain[1] as u32,
             },
             Interaction::default(),
         ))
@@ -242,7 +244,7 @@ fn spawn_bee_counter(
                         top: Val::Percent(-4.0),
                         ..default()
                     },
-                    material: materials.add(kind.into()),
+                    material: materials.add(stats.get_material()),
                     ..MaterialNodeBundle::default()
                 },
                 BeeCounter { kind },
@@ -357,6 +359,7 @@ pub fn update_counter(
     hive_buildings: Res<HiveBuildings>,
     mut materials: ResMut<Assets<UniversalMaterial>>,
     mouse: Res<MouseState>,
+    units: Res<UnitRegistry>,
 ) {
     for (mut counter, mut text) in counters_text.iter_mut() {
         if counter.kind == BeeType::Queen {
@@ -375,7 +378,7 @@ pub fn update_counter(
     for (mut counter, mut material) in counters_images.iter_mut() {
         if counter.kind != hive_buildings.get_current_level(counter.kind) {
             counter.kind = hive_buildings.get_current_level(counter.kind);
-            *material = materials.add(counter.kind.into());
+            *material = materials.add(units.get(counter.kind).get_material());
         }
     }
     for (mut counter, mut text) in future_counters_text.iter_mut() {
@@ -421,13 +424,12 @@ pub fn update_counter(
         if let Some(bee) = stats_target.bee {
             if bee != hive_buildings.get_current_level(bee) {
                 stats_target.bee = Some(hive_buildings.get_current_level(bee));
-                let creature = LivingCreature::from(stats_target.bee.unwrap());
-                stats_target.attack = creature.attack_damage;
-                stats_target.cooldown = creature.attack_cooldown;
-                stats_target.health = creature.health as u32;
-                let gain = CurrencyGainPerMinute::from(stats_target.bee.unwrap()).gain;
-                stats_target.honey = gain[0] as u32;
-                stats_target.wax = gain[1] as u32;
+                let bee_stats = units.get(stats_target.bee.unwrap());
+                stats_target.attack = bee_stats.attack_damage;
+                stats_target.cooldown = bee_stats.attack_cooldown;
+                stats_target.health = bee_stats.health as u32;
+                stats_target.honey = bee_stats.gain[0] as u32;
+                stats_target.wax = bee_stats.gain[1] as u32;
             }
         }
 
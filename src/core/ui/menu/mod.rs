@@ -1,5 +1,6 @@
 use crate::core::{
-    get_building_image_name, Building, BuildingKind, HiveBuildings, UniversalMaterial, GameInfo,
+    get_building_image_name, BuildRequested, Building, BuildingKind, DestroyRequested,
+    HiveBuildings, OrderRequested, UniversalMaterial, UnitRegistry, UpgradeRequested,
 };
 
 use self::{
@@ -102,8 +103,9 @@ pub fn menu_update(
     mut time_since_closing: Local<Option<f32>>,
     mut content_exists: Local<bool>,
     hive_buildings: Res<HiveBuildings>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
     mut materials: ResMut<Assets<UniversalMaterial>>,
+    units: Res<UnitRegistry>,
 ) {
     if content.iter().count() == 0 {
         return;
@@ -169,6 +171,7 @@ pub fn menu_update(
                                 &hive_buildings,
                                 &asset_server,
                                 &mut materials,
+                                &units,
                             )
                         });
                     };
@@ -191,11 +194,13 @@ pub fn click_button_system(
         ),
         Changed<Interaction>,
     >,
-    mut buildings: Query<&mut Building>,
-    mut hive_buildings: ResMut<HiveBuildings>,
-    game: Res<GameInfo>,
+    mut builds: EventWriter<BuildRequested>,
+    mut upgrades: EventWriter<UpgradeRequested>,
+    mut destroys: EventWriter<DestroyRequested>,
+    mut orders: EventWriter<OrderRequested>,
+    virtual_time: Res<Time<Virtual>>,
 ) {
-    if game.paused {
+    if virtual_time.is_paused() {
         return;
     }
     for (
@@ -214,25 +219,29 @@ pub fn click_button_system(
         {
             // Order button
             if let Some(order) = maybe_order {
-                for mut b in buildings.iter_mut() {
-                    if b.index != order.building_index {
-                        continue;
-                    }
-                    b.order();
-                }
+                orders.send(OrderRequested {
+                    index: order.building_index,
+                });
             }
 
             // Build button
             if let Some(build) = maybe_build {
-                hive_buildings.build_order = Some((build.kind, build.index));
+                builds.send(BuildRequested {
+                    index: build.index,
+                    kind: build.kind,
+                });
             }
             // Upgrade button
             if let Some(upgrade) = maybe_upgrade {
-                hive_buildings.upgrade_order = Some(upgrade.building_index);
+                upgrades.send(UpgradeRequested {
+                    index: upgrade.building_index,
+                });
             }
             // Destroy button
             if let Some(destroy) = maybe_destroy {
-                hive_buildings.destroy_order = Some(destroy.building_index);
+                destroys.send(DestroyRequested {
+                    index: destroy.building_index,
+                });
             }
         }
 
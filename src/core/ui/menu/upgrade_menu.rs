@@ -5,7 +5,7 @@ use crate::core::{
         button::{spawn_button, EnableButtonWhenHaveMoney},
         constants,
     },
-    Building, BuildingKind, HiveBuildings, RelativePixelSized, UniversalMaterial, CURRENCY_NUM, MAX_WORKER_LEVEL, MAX_DEFENDER_LEVEL,
+    Building, BuildingKind, HiveBuildings, RelativePixelSized, UniversalMaterial, UnitRegistry, CURRENCY_NUM, MAX_WORKER_LEVEL, MAX_DEFENDER_LEVEL, MAX_SHIELD_LEVEL,
 };
 
 #[derive(Component)]
@@ -29,6 +29,7 @@ pub fn spawn_upgrage_menu(
     hive_buildings: &HiveBuildings,
     asset_server: &AssetServer,
     materials: &mut Assets<UniversalMaterial>,
+    units: &UnitRegistry,
 ) {
     let kind = hive_buildings.buildings[building_index];
     let (width, height) = kind.get_menu_size();
@@ -71,7 +72,7 @@ pub fn spawn_upgrage_menu(
                             ..default()
                         },
                         z_index: ZIndex::Global(20),
-                        material: materials.add(bee.into()),
+                        material: materials.add(units.get(bee).get_material()),
                         ..MaterialNodeBundle::default()
                     },
                     RelativePixelSized {
@@ -90,7 +91,7 @@ pub fn spawn_upgrage_menu(
                             ..default()
                         },
                         z_index: ZIndex::Global(20),
-                        material: materials.add(bee.into()),
+                        material: materials.add(units.get(bee).get_material()),
                         ..MaterialNodeBundle::default()
                     },
                     RelativePixelSized {
@@ -139,7 +140,7 @@ pub fn spawn_upgrage_menu(
                 },
             );
 
-            if kind != BuildingKind::WaxReactor && kind != BuildingKind::MagicWaxReactor && kind != BuildingKind::Nexus {
+            if kind != BuildingKind::WaxReactor && kind != BuildingKind::MagicWaxReactor {
                 let mut cost = hive_buildings.get_upgrade_cost(kind);
                 let mut a = if cost[0] != 0 {
                     Some(cost[0])
@@ -157,7 +158,8 @@ pub fn spawn_upgrage_menu(
                 };
 
                 if kind == BuildingKind::Workshop && hive_buildings.worker_lvl >= MAX_WORKER_LEVEL - 1 ||
-                    kind == BuildingKind::Armory && hive_buildings.defender_lvl >= MAX_DEFENDER_LEVEL - 1 {
+                    kind == BuildingKind::Armory && hive_buildings.defender_lvl >= MAX_DEFENDER_LEVEL - 1 ||
+                    kind == BuildingKind::Nexus && hive_buildings.shield_lvl >= MAX_SHIELD_LEVEL {
                     a = Some(0);
                     b = Some(0);
                     cost = [99999999; CURRENCY_NUM];
@@ -6,8 +6,8 @@ use self::{
     moving_ui::move_ui,
 };
 use super::{
-    get_building_position, AppState, Building, BuildingKind, BuildingMaterial, CurrencyStorage,
-    HiveBuildings, MouseState, UniversalMaterial,
+    AppState, Building, BuildingKind, BuildingMaterial, CurrencyStorage,
+    HiveBuildings, MouseState, UniversalMaterial, UnitRegistry,
 };
 use bevy::prelude::*;
 
@@ -177,6 +177,7 @@ fn setup_game_ui(
     mut commands: Commands,
     materials: ResMut<Assets<UniversalMaterial>>,
     mut asset_server: ResMut<AssetServer>,
+    units: Res<UnitRegistry>,
 ) {
     commands
         .spawn((
@@ -192,7 +193,7 @@ fn setup_game_ui(
             Interaction::None,
         ))
         .with_children(|builder| {
-            setup_bee_counters(builder, materials, &mut asset_server);
+            setup_bee_counters(builder, materials, &mut asset_server, &units);
             spawn_currency_display(builder, &mut asset_server);
             spawn_menu(builder, &mut asset_server);
         });
@@ -203,6 +204,7 @@ fn highlight_hive(
     mut building_menu_query: Query<&mut Menu>,
     buildings_query: Query<(Entity, &Building, &Handle<BuildingMaterial>)>,
     mouse: Res<MouseState>,
+    hive_buildings: Res<HiveBuildings>,
     mut materials: ResMut<Assets<BuildingMaterial>>,
     mut mouse_pos_when_pressed: Local<Vec2>,
 ) {
@@ -226,7 +228,7 @@ fn highlight_hive(
             // Button released and mouse did not move
             building_menu.focus_building = None;
             for (_, building, _) in buildings_query.iter() {
-                let building_position = get_building_position(building.index);
+                let building_position = hive_buildings.get_building_position(building.index);
                 if mouse_position.distance(building_position) < 32.0
                     && building.kind != BuildingKind::Storage
                 {
@@ -259,7 +261,7 @@ fn highlight_hive(
 
     if let Some(mouse_position) = mouse.position {
         for (_, building, material) in buildings_query.iter() {
-            let building_position = get_building_position(building.index);
+            let building_position = hive_buildings.get_building_position(building.index);
 
             if let Some(material) = materials.get_mut(material) {
                 if mouse_position.distance(building_position) < 32.0 {
@@ -15,7 +15,7 @@ pub struct MovingUi {
     pub target: Target,
 }
 
-pub fn move_ui(mut mui: Query<(&MovingUi, &mut Style)>, ui: Res<UiSize>, time: Res<Time>) {
+pub fn move_ui(mut mui: Query<(&MovingUi, &mut Style)>, ui: Res<UiSize>, time: Res<Time<Real>>) {
     for (mui, mut style) in mui.iter_mut() {
         let limit = ui.size as f32 * time.delta_seconds() * 950.0;
         style.right = transform(style.right, mui.target.right, limit);
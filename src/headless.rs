@@ -0,0 +1,97 @@
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    input::InputPlugin,
+    prelude::*,
+    render::texture::{CompressedImageFormats, ImageSampler, ImageType},
+    time::TimeUpdateStrategy,
+};
+
+use crate::{
+    core::{
+        AppState, HiveDefinition, HiveGraph, HiveMap, ModelPlugin, NavigationPlugin,
+        RunSeedOverride, SIMULATION_HZ,
+    },
+    levels::{LevelsPlugin, ScenarioHives, ScenarioRegistry, SelectedScenario},
+};
+
+// Every update runs exactly one fixed step
+pub fn headless_app(seed: u64, scenario: usize) -> Result<App, String> {
+    let hive_map = load_hive_map(scenario)?;
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AssetPlugin::default())
+        .add_plugins(InputPlugin);
+    app.init_asset::<Mesh>();
+    app.add_state::<AppState>();
+
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / SIMULATION_HZ,
+    )));
+    app.insert_resource(hive_map);
+    app.insert_resource(RunSeedOverride(Some(seed)));
+    app.insert_resource(SelectedScenario(scenario));
+
+    app.add_plugins((ModelPlugin, NavigationPlugin, LevelsPlugin));
+    Ok(app)
+}
+
+pub const HEADLESS_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Waits for the navigation graph and hive layout, so the first tick is the same every time
+pub fn start_headless_run(app: &mut App) -> Result<(), String> {
+    app.finish();
+    app.cleanup();
+
+    let start = Instant::now();
+    loop {
+        let graph_ready = app.world.resource::<HiveGraph>().ready;
+        let hives_loaded = app
+            .world
+            .resource::<ScenarioHives>()
+            .is_loaded(app.world.resource::<Assets<HiveDefinition>>());
+        if graph_ready && hives_loaded {
+            break;
+        }
+        if start.elapsed() > HEADLESS_STARTUP_TIMEOUT {
+            let missing = if graph_ready {
+                "the scenario hives"
+            } else {
+                "the navigation graph"
+            };
+            return Err(format!(
+                "{} not ready after {:?}",
+                missing, HEADLESS_STARTUP_TIMEOUT
+            ));
+        }
+        app.update();
+    }
+
+    app.world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::InGame);
+    Ok(())
+}
+
+// Read straight from the assets, there is no image loading without a renderer
+pub fn load_hive_map(scenario: usize) -> Result<HiveMap, String> {
+    let path = format!("assets/{}", ScenarioRegistry::default().get(scenario).hive);
+    let bytes = fs::read(&path).map_err(|e| format!("{}: {}", path, e))?;
+    let hive = HiveDefinition::from_slice(&bytes).map_err(|e| format!("{}: {}", path, e))?;
+
+    let path = format!("assets/{}", hive.image);
+    let bytes = fs::read(&path).map_err(|e| format!("{}: {}", path, e))?;
+    let image = Image::from_buffer(
+        &bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(HiveMap::from_image(&image, &hive))
+}
@@ -0,0 +1,118 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::core::{CurrencyValues, EnemyType};
+
+use super::{EnemySpawn, WaveDefinition};
+
+/// Enemies the generator can pick from together with their difficulty cost.
+const ENEMY_COSTS: [(EnemyType, f32); 8] = [
+    (EnemyType::Wasp(0), 1.0),
+    (EnemyType::Wasp(1), 2.0),
+    (EnemyType::Birb(0), 5.0),
+    (EnemyType::Bumble(0), 6.0),
+    (EnemyType::Birb(1), 8.0),
+    (EnemyType::Bumble(1), 10.0),
+    (EnemyType::Birb(2), 14.0),
+    (EnemyType::Bumble(2), 18.0),
+];
+
+/// Generated waves never have more enemies than this, the budget left over
+/// levels them up instead.
+pub const MAX_ENDLESS_ENEMIES: usize = 40;
+/// Every level past the base one multiplies the cost of an enemy by this.
+pub const ENDLESS_LEVEL_COST_GROWTH: f32 = 1.5;
+pub const MAX_ENDLESS_EXTRA_LEVELS: u32 = 30;
+
+/// Builds waves after the scripted ones run out. Every round is generated
+/// from `seed`, the run seed and the round number only, so the same seeds
+/// always give the same sequence of waves.
+#[derive(Deserialize, Clone, Debug)]
+pub struct EndlessWaveGenerator {
+    #[serde(default)]
+    pub seed: u64,
+    pub delay: f32,
+    /// Difficulty budget of the first generated wave.
+    pub base_budget: f32,
+    /// Budget multiplier applied every round.
+    pub budget_growth: f32,
+    /// The most expensive enemy can take at most this part of the budget.
+    pub max_single_enemy_share: f32,
+    /// Elite chance of the generated waves.
+    #[serde(default)]
+    pub elite_chance: f32,
+}
+
+impl EndlessWaveGenerator {
+    pub fn get_budget(&self, round: usize) -> f32 {
+        let round = round.min(i32::MAX as usize) as i32;
+        self.base_budget * self.budget_growth.powi(round)
+    }
+
+    pub fn generate(&self, round: usize, run_seed: u64) -> WaveDefinition {
+        let mut rng = StdRng::seed_from_u64(
+            (self.seed ^ run_seed)
+                .wrapping_mul(0x9E37_79B9_7F4A_7C15)
+                .wrapping_add(round as u64),
+        );
+
+        let budget = self.get_budget(round);
+        let max_single_cost = (budget * self.max_single_enemy_share).max(ENEMY_COSTS[0].1);
+
+        let mut spent = 0.0;
+        let mut picked: Vec<(EnemyType, f32)> = vec![];
+
+        while picked.len() < MAX_ENDLESS_ENEMIES {
+            let candidates: Vec<(EnemyType, f32)> = ENEMY_COSTS
+                .iter()
+                .copied()
+                .filter(|(_, cost)| spent + *cost <= budget && *cost <= max_single_cost)
+                .collect();
+
+            if candidates.is_empty() {
+                break;
+            }
+
+            let pick = candidates[rng.gen_range(0..candidates.len())];
+            spent += pick.1;
+            picked.push(pick);
+        }
+
+        // Big enemies lead the wave, just like in the scripted ones
+        picked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        // The drop pays for the leveled up enemy, not the one that was picked
+        let extra_levels = get_extra_levels(budget, spent);
+        let cost_growth = ENDLESS_LEVEL_COST_GROWTH.powi(extra_levels as i32);
+        let enemies = picked
+            .into_iter()
+            .map(|(enemy, cost)| EnemySpawn {
+                enemy: enemy.with_level(enemy.get_level() + extra_levels),
+                drop: get_drop(cost * cost_growth),
+                boss: false,
+            })
+            .collect();
+
+        WaveDefinition {
+            delay: self.delay,
+            side: None,
+            enemies,
+            elite_chance: self.elite_chance,
+        }
+    }
+}
+
+/// How many levels every enemy of a full wave can get out of the budget that
+/// is left over.
+fn get_extra_levels(budget: f32, spent: f32) -> u32 {
+    if spent <= 0.0 || budget <= spent {
+        return 0;
+    }
+    let levels = (budget / spent).ln() / ENDLESS_LEVEL_COST_GROWTH.ln();
+    (levels.floor() as u32).min(MAX_ENDLESS_EXTRA_LEVELS)
+}
+
+fn get_drop(cost: f32) -> CurrencyValues {
+    let cost = cost as u64;
+    [6 + 4 * cost, 1 + cost, cost.div_ceil(2)]
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+
+use crate::core::{RelativePixelFont, FONT_HANDLE};
+
+use super::{ScenarioRegistry, SelectedScenario};
+
+#[derive(Component)]
+pub struct LevelSelectItem {
+    pub index: usize,
+}
+
+#[derive(Component)]
+pub struct LevelBriefing;
+
+const SELECTED_COLOR: Color = Color::rgb(0.3, 0.23, 0.0);
+const NOT_SELECTED_COLOR: Color = Color::rgba(0.3, 0.23, 0.0, 0.45);
+
+pub fn spawn_level_select(mut commands: Commands, registry: Res<ScenarioRegistry>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::ZERO,
+                top: Val::Percent(62.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|builder| {
+            for (index, scenario) in registry.scenarios.iter().enumerate() {
+                builder.spawn((
+                    TextBundle {
+                        text: Text::from_section(
+                            scenario.name.clone(),
+                            TextStyle {
+                                font: FONT_HANDLE,
+                                font_size: 20.0,
+                                color: NOT_SELECTED_COLOR,
+                            },
+                        ),
+                        style: Style {
+                            margin: UiRect::bottom(Val::Percent(0.5)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Interaction::None,
+                    RelativePixelFont { size: 12 },
+                    LevelSelectItem { index },
+                ));
+            }
+
+            builder.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: FONT_HANDLE,
+                            font_size: 20.0,
+                            color: SELECTED_COLOR,
+                        },
+                    ),
+                    style: Style {
+                        margin: UiRect::top(Val::Percent(1.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                RelativePixelFont { size: 8 },
+                LevelBriefing,
+            ));
+        });
+}
+
+pub fn level_select_system(
+    items: Query<(&Interaction, &LevelSelectItem), Changed<Interaction>>,
+    added: Query<(), Added<LevelSelectItem>>,
+    mut texts: Query<(&mut Text, &LevelSelectItem), Without<LevelBriefing>>,
+    mut briefing: Query<&mut Text, With<LevelBriefing>>,
+    registry: Res<ScenarioRegistry>,
+    mut selected: ResMut<SelectedScenario>,
+) {
+    for (interaction, item) in items.iter() {
+        if *interaction == Interaction::Pressed && selected.0 != item.index {
+            selected.0 = item.index;
+        }
+    }
+
+    if !selected.is_changed() && added.is_empty() {
+        return;
+    }
+
+    for (mut text, item) in texts.iter_mut() {
+        text.sections[0].style.color = if item.index == selected.0 {
+            SELECTED_COLOR
+        } else {
+            NOT_SELECTED_COLOR
+        };
+    }
+
+    let scenario = registry.get(selected.0);
+    for mut text in briefing.iter_mut() {
+        text.sections[0].value = scenario.briefing.clone().unwrap_or_default();
+    }
+}
@@ -1,48 +1,132 @@
 use bevy::{prelude::*, render::mesh::shape::Quad, sprite::Mesh2dHandle};
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 use crate::core::{
-    EnemyType, Faction, LivingCreature, MoveToNavigationTargetBehaviour, NavigationResult,
-    NavigationTarget, RigidBody, SmartOrientation, UniversalBehaviour, MAX_VIEW_RECT, GameInfo, CurrencyValues,
+    AppState, EnemyType, GameRng, PreviousTranslation, RngStream, LogicSet, Faction, LivingCreature, MoveToNavigationTargetBehaviour, NavigationResult,
+    NavigationTarget, SmartOrientation, MAX_VIEW_RECT, CurrencyValues, HiveDefinition, HiveDefinitionLoader,
+    UnitRegistry, Elite, EliteAffix, living_creature_system, assign_spawn_order, get_spawn_ordered,
+    SpawnOrder,
 };
+use crate::utils::Direction;
+
+mod endless;
+mod level_select;
+mod scenario;
+mod waves;
+
+pub use endless::*;
+pub use level_select::*;
+pub use scenario::*;
+pub use waves::*;
+
 pub struct LevelsPlugin;
 
 impl Plugin for LevelsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, scenario0_system);
+        app.init_asset::<WaveSet>();
+        app.register_asset_loader(WaveSetLoader);
+        app.init_asset::<HiveDefinition>();
+        app.register_asset_loader(HiveDefinitionLoader);
+
+        app.init_resource::<ScenarioRegistry>();
+        app.init_resource::<SelectedScenario>();
+        app.init_resource::<ScenarioHives>();
+        app.add_systems(Startup, load_scenario_hives_system);
+        app.add_event::<WaveCleared>();
+
+        app.add_systems(
+            FixedUpdate,
+            // Bosses spawned this tick must exist before the win conditions are checked
+            (scenario_system, apply_deferred, scenario_conditions_system)
+                .chain()
+                .in_set(LogicSet::Waves),
+        );
+        app.add_systems(
+            FixedUpdate,
+            // Dead enemies are still there to split before they are despawned
+            elite_split_system
+                .before(living_creature_system)
+                .in_set(LogicSet::Upkeep),
+        );
+        app.add_systems(Update, log_wave_set_events_system);
+
+        app.add_systems(
+            Last,
+            spawn_level_select
+                .run_if(state_changed::<AppState>().and_then(in_state(AppState::MainMenu))),
+        );
+        app.add_systems(
+            Update,
+            level_select_system.run_if(in_state(AppState::MainMenu)),
+        );
     }
 }
 
 #[derive(Component, Default)]
-pub struct Scenario0 {
+pub struct Scenario {
+    /// Index in the [`ScenarioRegistry`].
+    pub index: usize,
     pub time_elapsed: f32,
     pub wave: usize,
+    pub waves: Handle<WaveSet>,
+    pub wave_active: bool,
+    pub waves_cleared: usize,
+    pub bosses_spawned: usize,
 }
 
+#[derive(Component)]
+pub struct Boss;
+
 #[derive(Component)]
 pub struct NextWave;
 
-pub fn scenario0_system(
-    mut scenarios: Query<(Entity, &mut Scenario0)>,
+/// Sent when the last enemy of a wave dies.
+#[derive(Event)]
+pub struct WaveCleared {
+    pub waves_cleared: usize,
+}
+
+pub fn scenario_system(
+    mut scenarios: Query<(Entity, &mut Scenario)>,
     enemis: Query<&EnemyType>,
+    wave_sets: Res<Assets<WaveSet>>,
     time: Res<Time>,
-    game: Res<GameInfo>,
+    mut game_rng: ResMut<GameRng>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut next_wave: Query<&mut Text, With<NextWave>>,
+    mut wave_cleared: EventWriter<WaveCleared>,
+    units: Res<UnitRegistry>,
 ) {
-    if game.paused {
-        return;
-    }
     if scenarios.is_empty() {
         return;
     }
 
     let (_, mut scenario) = scenarios.single_mut();
 
+    let Some(wave_set) = wave_sets.get(&scenario.waves) else {
+        return;
+    };
+
+    if !wave_set.is_endless() {
+        // The wave set could have been hot-reloaded with fewer waves
+        scenario.wave = wave_set.get_scripted_index(scenario.wave);
+    }
+    let delay = wave_set.get_delay(scenario.wave);
+
     if enemis.is_empty() {
+        if scenario.wave_active {
+            scenario.wave_active = false;
+            scenario.waves_cleared += 1;
+            wave_cleared.send(WaveCleared {
+                waves_cleared: scenario.waves_cleared,
+            });
+        }
         for mut text in next_wave.iter_mut() {
-            text.sections[0].value = format!("Next wave in {}...", 40 - scenario.time_elapsed as i32);
+            text.sections[0].value = format!(
+                "Next wave in {}...",
+                delay as i32 - scenario.time_elapsed as i32
+            );
         }
         scenario.time_elapsed += time.delta_seconds();
     } else {
@@ -51,184 +135,37 @@ pub fn scenario0_system(
         }
     }
 
-    if scenario.time_elapsed < 40.0 {
+    if scenario.time_elapsed < delay {
         return;
     }
 
     scenario.time_elapsed = 0.0;
+    scenario.wave_active = true;
 
-    let waves = vec![
-        vec![
-            (EnemyType::Wasp(0), [16, 2, 1]),
-        ],
-        vec![
-            (EnemyType::Wasp(0), [14, 2, 1]),
-            (EnemyType::Wasp(0), [14, 2, 1]),
-        ],
-        vec![
-            (EnemyType::Wasp(0), [12, 2, 2]),
-            (EnemyType::Wasp(0), [12, 2, 1]),
-            (EnemyType::Wasp(0), [12, 2, 2]),
-        ],
-        vec![
-            (EnemyType::Wasp(0), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 3]),
-            (EnemyType::Wasp(1), [10, 2, 3]),
-        ],
-        vec![
-            (EnemyType::Birb(0), [40, 16, 8]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-        ],
-        vec![
-            (EnemyType::Birb(0), [40, 16, 8]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-        ],
-        vec![
-            (EnemyType::Bumble(0), [40, 16, 8]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-        ],
-        vec![
-            (EnemyType::Birb(1), [40, 8, 8]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-        ],
-        vec![
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-        ],
-        vec![
-            (EnemyType::Bumble(1), [20, 8, 8]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-        ],
-        vec![
-            (EnemyType::Birb(2), [20, 8, 8]),
-            (EnemyType::Birb(2), [20, 8, 8]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-        ],
-        vec![
-            (EnemyType::Bumble(2), [20, 4, 8]),
-            (EnemyType::Bumble(2), [20, 4, 8]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-        ],
-        vec![
-            (EnemyType::Bumble(2), [20, 4, 8]),
-            (EnemyType::Bumble(2), [20, 4, 8]),
-            (EnemyType::Birb(2), [20, 8, 8]),
-            (EnemyType::Birb(2), [20, 8, 8]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-        ],
-        vec![
-            (EnemyType::Bumble(2), [20, 4, 8]),
-            (EnemyType::Bumble(2), [20, 4, 8]),
-            (EnemyType::Birb(2), [20, 4, 8]),
-            (EnemyType::Birb(2), [20, 4, 8]),
-            (EnemyType::Birb(2), [20, 2, 2]),
-            (EnemyType::Birb(2), [20, 2, 2]),
-            (EnemyType::Bumble(1), [20, 2, 2]),
-            (EnemyType::Bumble(1), [20, 2, 2]),
-            (EnemyType::Birb(1), [20, 2, 2]),
-            (EnemyType::Birb(1), [20, 2, 2]),
-            (EnemyType::Bumble(0), [20, 2, 2]),
-            (EnemyType::Bumble(0), [20, 2, 2]),
-            (EnemyType::Birb(0), [20, 2, 2]),
-            (EnemyType::Birb(0), [20, 2, 2]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-            (EnemyType::Wasp(1), [10, 2, 1]),
-        ],
-    ];
+    let wave = wave_set.get_wave(scenario.wave, game_rng.seed());
+    // Elites have their own stream, so adding them to a wave doesn't move its enemies
+    let elite_rng = game_rng.stream(RngStream::Elites);
+    let elites: Vec<Option<Elite>> = wave
+        .enemies
+        .iter()
+        .map(|_| {
+            (wave.elite_chance > 0.0 && elite_rng.gen_bool(wave.elite_chance as f64))
+                .then(|| Elite::roll(elite_rng))
+        })
+        .collect();
 
-    for (enemy, drop) in waves[scenario.wave].iter() {
-        spawn_enemy(&mut commands, *enemy, &mut meshes, *drop);
+    let rng = game_rng.stream(RngStream::Waves);
+    for (spawn, elite) in wave.enemies.iter().zip(elites) {
+        let enemy = spawn_enemy(&mut commands, rng, spawn.enemy, &mut meshes, &units, spawn.drop, wave.side, elite);
+        if spawn.boss {
+            commands.entity(enemy).insert(Boss);
+            scenario.bosses_spawned += 1;
+        }
     }
 
-    if scenario.wave + 1 < waves.len() {
+    if wave_set.is_endless() || scenario.wave + 1 < wave_set.waves.len() {
         scenario.wave += 1;
     }
-    
 }
 
 fn get_size(value: EnemyType) -> f32 {
@@ -239,10 +176,25 @@ fn get_size(value: EnemyType) -> f32 {
     }
 }
 
-fn spawn_enemy(commands: &mut Commands, enemy: EnemyType, meshes: &mut Assets<Mesh>, drop: CurrencyValues) {
-    let dx = thread_rng().gen_range(-1.0..1.0);
-    let dy = thread_rng().gen_range(-1.0..1.0);
-    let z = thread_rng().gen_range(0.0..1.0);
+fn spawn_enemy(
+    commands: &mut Commands,
+    rng: &mut impl Rng,
+    enemy: EnemyType,
+    meshes: &mut Assets<Mesh>,
+    units: &UnitRegistry,
+    drop: CurrencyValues,
+    side: Option<Direction>,
+    elite: Option<Elite>,
+) -> Entity {
+    let (dx, dy) = if let Some(side) = side {
+        let forward: Vec2 = side.into();
+        let spread = forward.perp() * rng.gen_range(-1.0..1.0);
+        let d = forward + spread;
+        (d.x, d.y)
+    } else {
+        (rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0))
+    };
+    let z = rng.gen_range(0.0..1.0);
 
     let t0 = if dx > 0.0 {
         MAX_VIEW_RECT.max.x / dx
@@ -257,21 +209,86 @@ fn spawn_enemy(commands: &mut Commands, enemy: EnemyType, meshes: &mut Assets<Me
     let t = t0.min(t1) / 2.0;
 
     let position = Vec3::new(dx * t, dy * t, z);
+    let stats = units.get(enemy);
+    let mut creature = LivingCreature {
+        currency_drop: stats.get_drop(drop),
+        ..stats.get_creature()
+    };
+    let Some(elite) = elite else {
+        return spawn_enemy_at(commands, enemy, meshes, units, position, creature);
+    };
+
+    let mut rigid_body = stats.get_rigid_body();
+    elite.apply(&mut creature, &mut rigid_body);
+    let shield = elite.get_shield(&creature);
+    let e = spawn_enemy_at(commands, enemy, meshes, units, position, creature);
+    commands.entity(e).insert((rigid_body, elite));
+    if let Some(shield) = shield {
+        commands.entity(e).insert(shield);
+    }
+    e
+}
+
+pub fn spawn_enemy_at(
+    commands: &mut Commands,
+    enemy: EnemyType,
+    meshes: &mut Assets<Mesh>,
+    units: &UnitRegistry,
+    position: Vec3,
+    creature: LivingCreature,
+) -> Entity {
+    let stats = units.get(enemy);
     commands.spawn((
         VisibilityBundle::default(),
         TransformBundle::from_transform(Transform::from_translation(position)),
+        PreviousTranslation(position),
         Mesh2dHandle(meshes.add(Quad::new(Vec2::splat(get_size(enemy))).into())),
         enemy,
-        LivingCreature {
-            currency_drop: drop,
-            ..LivingCreature::from(enemy)
-        },
-        RigidBody::from(enemy),
-        UniversalBehaviour::from(enemy),
+        creature,
+        stats.get_rigid_body(),
+        stats.get_behaviour(),
         NavigationTarget::None,
         NavigationResult::default(),
         MoveToNavigationTargetBehaviour,
         SmartOrientation,
         Faction::Enemies,
-    ));
+    )).add(assign_spawn_order).id()
+}
+
+/// Offset of the wasps a [`EliteAffix::Splitting`] enemy falls apart into.
+const SPLIT_OFFSET: f32 = 8.0;
+
+/// Splits a dead [`EliteAffix::Splitting`] enemy into two wasps a level lower,
+/// each dropping half of its currency. Level 0 enemies have nothing smaller to
+/// split into.
+pub fn elite_split_system(
+    mut commands: Commands,
+    mut elites: Query<(&Transform, &EnemyType, &LivingCreature, &mut Elite)>,
+    orders: Query<(Entity, Option<&SpawnOrder>), With<Elite>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    units: Res<UnitRegistry>,
+) {
+    // The wasps are numbered in the order they are spawned in
+    for e in get_spawn_ordered(orders.iter()) {
+        let Ok((transform, enemy, creature, mut elite)) = elites.get_mut(e) else {
+            continue;
+        };
+        if !creature.is_dead() || !elite.has(EliteAffix::Splitting) {
+            continue;
+        }
+        elite.affixes.retain(|affix| *affix != EliteAffix::Splitting);
+
+        let Some(level) = enemy.get_level().checked_sub(1) else {
+            continue;
+        };
+        let wasp = EnemyType::Wasp(level);
+        for side in [-1.0, 1.0] {
+            let position = transform.translation + Vec3::new(side * SPLIT_OFFSET, 0.0, 0.0);
+            let creature = LivingCreature {
+                currency_drop: creature.currency_drop.map(|amount| amount / 2),
+                ..units.get(wasp).get_creature()
+            };
+            spawn_enemy_at(&mut commands, wasp, &mut meshes, &units, position, creature);
+        }
+    }
 }
@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+
+use crate::core::{
+    BuildingKind, CurrencyStorage, CurrencyValues, GameInfo, HiveBuildings, HiveDefinition,
+};
+
+use super::{Boss, Scenario};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WinCondition {
+    /// Clear this many waves.
+    SurviveWaves(usize),
+    /// Have at least this much of every currency stored at once.
+    ReachCurrency(CurrencyValues),
+    /// Kill every enemy spawned as a boss by the wave set.
+    KillBoss,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LoseCondition {
+    /// Makes the Queen end the game when she dies.
+    QueenDied,
+    /// Lose once the Nexus is destroyed by enemies.
+    NexusDestroyed,
+}
+
+pub struct ScenarioDefinition {
+    pub name: String,
+    pub briefing: Option<String>,
+    /// Path to the `.hive.json` asset.
+    pub hive: String,
+    /// Buildings placed on the hive lots at the start, the rest are empty.
+    pub buildings: Vec<(usize, BuildingKind)>,
+    pub currency: CurrencyValues,
+    /// Path to the `.waves.json` asset.
+    pub waves: String,
+    pub win_conditions: Vec<WinCondition>,
+    pub lose_conditions: Vec<LoseCondition>,
+}
+
+impl ScenarioDefinition {
+    /// One building per slot of `hive`, buildings on slots the hive doesn't
+    /// have are left out.
+    pub fn get_buildings(&self, hive: &HiveDefinition) -> Vec<BuildingKind> {
+        let mut buildings = vec![BuildingKind::None; hive.get_slots_num()];
+        for (index, kind) in self.buildings.iter() {
+            match buildings.get_mut(*index) {
+                Some(building) => *building = *kind,
+                None => warn!("{} has no building slot {} for {:?}", self.name, index, kind),
+            }
+        }
+        buildings
+    }
+}
+
+#[derive(Resource)]
+pub struct ScenarioRegistry {
+    pub scenarios: Vec<ScenarioDefinition>,
+}
+
+impl Default for ScenarioRegistry {
+    fn default() -> Self {
+        Self {
+            scenarios: vec![
+                ScenarioDefinition {
+                    name: String::from("Endless swarm"),
+                    briefing: Some(String::from(
+                        "Keep the Queen alive for as long as you can.",
+                    )),
+                    hive: String::from("hives/default.hive.json"),
+                    buildings: vec![(8, BuildingKind::Nexus)],
+                    currency: [20, 0, 0],
+                    waves: String::from("waves/scenario0.waves.json"),
+                    win_conditions: vec![],
+                    lose_conditions: vec![LoseCondition::QueenDied],
+                },
+                ScenarioDefinition {
+                    name: String::from("First swarm"),
+                    briefing: Some(String::from(
+                        "The schools are already built. Survive 5 waves and don't lose the birther.",
+                    )),
+                    hive: String::from("hives/default.hive.json"),
+                    buildings: vec![
+                        (8, BuildingKind::Nexus),
+                        (4, BuildingKind::Workshop),
+                        (6, BuildingKind::Armory),
+                    ],
+                    currency: [60, 10, 0],
+                    waves: String::from("waves/first_swarm.waves.json"),
+                    win_conditions: vec![WinCondition::SurviveWaves(5)],
+                    lose_conditions: vec![LoseCondition::QueenDied, LoseCondition::NexusDestroyed],
+                },
+                ScenarioDefinition {
+                    name: String::from("Honey rush"),
+                    briefing: Some(String::from(
+                        "Store 300 honey and 60 wax before the swarm gets you.",
+                    )),
+                    hive: String::from("hives/default.hive.json"),
+                    buildings: vec![(8, BuildingKind::Nexus)],
+                    currency: [20, 0, 0],
+                    waves: String::from("waves/scenario0.waves.json"),
+                    win_conditions: vec![WinCondition::ReachCurrency([300, 60, 0])],
+                    lose_conditions: vec![LoseCondition::QueenDied],
+                },
+                ScenarioDefinition {
+                    name: String::from("Bumble king"),
+                    briefing: Some(String::from(
+                        "A huge bumble is coming for the hive. Kill it.",
+                    )),
+                    hive: String::from("hives/default.hive.json"),
+                    buildings: vec![(8, BuildingKind::Nexus), (6, BuildingKind::Armory)],
+                    currency: [40, 20, 0],
+                    waves: String::from("waves/bumble_king.waves.json"),
+                    win_conditions: vec![WinCondition::KillBoss],
+                    lose_conditions: vec![LoseCondition::QueenDied],
+                },
+            ],
+        }
+    }
+}
+
+impl ScenarioRegistry {
+    pub fn get(&self, index: usize) -> &ScenarioDefinition {
+        &self.scenarios[index.min(self.scenarios.len() - 1)]
+    }
+}
+
+/// Hive of every scenario in the [`ScenarioRegistry`], by index.
+#[derive(Resource, Default)]
+pub struct ScenarioHives(pub Vec<Handle<HiveDefinition>>);
+
+impl ScenarioHives {
+    pub fn get<'a>(
+        &self,
+        index: usize,
+        hives: &'a Assets<HiveDefinition>,
+    ) -> Option<&'a HiveDefinition> {
+        let handle = self.0.get(index.min(self.0.len().saturating_sub(1)))?;
+        hives.get(handle)
+    }
+
+    /// Runs have to wait for these, the hive decides where everything is.
+    pub fn is_loaded(&self, hives: &Assets<HiveDefinition>) -> bool {
+        self.0.iter().all(|handle| hives.contains(handle))
+    }
+}
+
+pub fn load_scenario_hives_system(
+    mut commands: Commands,
+    registry: Res<ScenarioRegistry>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(ScenarioHives(
+        registry
+            .scenarios
+            .iter()
+            .map(|scenario| asset_server.load(&scenario.hive))
+            .collect(),
+    ));
+}
+
+/// Scenario that will be started by the Play button.
+#[derive(Resource, Default)]
+pub struct SelectedScenario(pub usize);
+
+pub fn scenario_conditions_system(
+    scenarios: Query<&Scenario>,
+    bosses: Query<(), With<Boss>>,
+    registry: Res<ScenarioRegistry>,
+    currency: Res<CurrencyStorage>,
+    hive_buildings: Res<HiveBuildings>,
+    mut game: ResMut<GameInfo>,
+) {
+    if game.end {
+        return;
+    }
+
+    for scenario in scenarios.iter() {
+        let definition = registry.get(scenario.index);
+
+        let won = definition.win_conditions.iter().any(|condition| match *condition {
+            WinCondition::SurviveWaves(waves) => scenario.waves_cleared >= waves,
+            WinCondition::ReachCurrency(target) => currency.check_can_spend(&target),
+            WinCondition::KillBoss => scenario.bosses_spawned > 0 && bosses.is_empty(),
+        });
+
+        if won {
+            game.end = true;
+            game.victory = true;
+            continue;
+        }
+
+        // The Queen ends the game on her own, see `LivingCreature::end_game_on_dead`
+        let nexus_destroyed = definition
+            .lose_conditions
+            .contains(&LoseCondition::NexusDestroyed)
+            && !hive_buildings.buildings.contains(&BuildingKind::Nexus);
+        if nexus_destroyed {
+            game.end = true;
+            game.nexus_destroyed = true;
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use std::{borrow::Cow, fmt};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    core::{CurrencyValues, EnemyType},
+    utils::Direction,
+};
+
+use super::EndlessWaveGenerator;
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct EnemySpawn {
+    pub enemy: EnemyType,
+    pub drop: CurrencyValues,
+    /// Killing all bosses wins scenarios with [`WinCondition::KillBoss`](super::WinCondition::KillBoss).
+    #[serde(default)]
+    pub boss: bool,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct WaveDefinition {
+    /// Seconds without enemies on the map before this wave is spawned.
+    pub delay: f32,
+    /// Side of the map the wave comes from, random for every enemy if not set.
+    #[serde(default)]
+    pub side: Option<Direction>,
+    pub enemies: Vec<EnemySpawn>,
+    /// Chance for every enemy of the wave to spawn as an [`Elite`](crate::core::Elite).
+    #[serde(default)]
+    pub elite_chance: f32,
+}
+
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct WaveSet {
+    pub waves: Vec<WaveDefinition>,
+    /// Generates waves once the scripted ones run out, otherwise the last
+    /// scripted wave repeats forever.
+    pub endless: Option<EndlessWaveGenerator>,
+}
+
+#[derive(Deserialize)]
+struct RawWaveSet {
+    waves: Vec<serde_json::Value>,
+    #[serde(default)]
+    endless: Option<EndlessWaveGenerator>,
+}
+
+#[derive(Debug)]
+pub enum WaveSetLoaderError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Empty,
+    InvalidWave { index: usize, reason: String },
+    InvalidEndless(String),
+}
+
+impl fmt::Display for WaveSetLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaveSetLoaderError::Io(e) => write!(f, "could not read wave set: {}", e),
+            WaveSetLoaderError::Json(e) => write!(f, "malformed wave set: {}", e),
+            WaveSetLoaderError::Empty => write!(f, "wave set has no waves"),
+            WaveSetLoaderError::InvalidWave { index, reason } => {
+                write!(f, "wave {} is invalid: {}", index, reason)
+            }
+            WaveSetLoaderError::InvalidEndless(reason) => {
+                write!(f, "endless generator is invalid: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WaveSetLoaderError {}
+
+impl From<std::io::Error> for WaveSetLoaderError {
+    fn from(value: std::io::Error) -> Self {
+        WaveSetLoaderError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for WaveSetLoaderError {
+    fn from(value: serde_json::Error) -> Self {
+        WaveSetLoaderError::Json(value)
+    }
+}
+
+fn check_delay(delay: f32) -> Result<(), String> {
+    if !delay.is_finite() || delay < 0.0 {
+        return Err(format!(
+            "delay must be non-negative and finite, got {}",
+            delay
+        ));
+    }
+    Ok(())
+}
+
+impl WaveSet {
+    pub fn from_slice(bytes: &[u8]) -> Result<WaveSet, WaveSetLoaderError> {
+        let raw: RawWaveSet = serde_json::from_slice(bytes)?;
+        if raw.waves.is_empty() {
+            return Err(WaveSetLoaderError::Empty);
+        }
+
+        let mut waves = Vec::with_capacity(raw.waves.len());
+        for (index, value) in raw.waves.into_iter().enumerate() {
+            let wave: WaveDefinition =
+                serde_json::from_value(value).map_err(|e| WaveSetLoaderError::InvalidWave {
+                    index,
+                    reason: e.to_string(),
+                })?;
+
+            if let Err(reason) = check_delay(wave.delay) {
+                return Err(WaveSetLoaderError::InvalidWave { index, reason });
+            }
+            if !(0.0..=1.0).contains(&wave.elite_chance) {
+                return Err(WaveSetLoaderError::InvalidWave {
+                    index,
+                    reason: format!("elite_chance must be in 0..=1, got {}", wave.elite_chance),
+                });
+            }
+            waves.push(wave);
+        }
+
+        if let Some(endless) = raw.endless.as_ref() {
+            if let Err(reason) = check_delay(endless.delay) {
+                return Err(WaveSetLoaderError::InvalidEndless(reason));
+            }
+            if endless.base_budget.is_nan() || endless.base_budget <= 0.0 {
+                return Err(WaveSetLoaderError::InvalidEndless(
+                    "base_budget must be positive".to_string(),
+                ));
+            }
+            if endless.budget_growth.is_nan() || endless.budget_growth < 1.0 {
+                return Err(WaveSetLoaderError::InvalidEndless(
+                    "budget_growth must be at least 1".to_string(),
+                ));
+            }
+            if endless.max_single_enemy_share.is_nan() || endless.max_single_enemy_share <= 0.0 {
+                return Err(WaveSetLoaderError::InvalidEndless(
+                    "max_single_enemy_share must be positive".to_string(),
+                ));
+            }
+            if !(0.0..=1.0).contains(&endless.elite_chance) {
+                return Err(WaveSetLoaderError::InvalidEndless(
+                    "elite_chance must be in 0..=1".to_string(),
+                ));
+            }
+        }
+
+        Ok(WaveSet {
+            waves,
+            endless: raw.endless,
+        })
+    }
+
+    pub fn is_endless(&self) -> bool {
+        self.endless.is_some()
+    }
+
+    /// Index of the wave that is actually played as wave `index`.
+    pub fn get_scripted_index(&self, index: usize) -> usize {
+        index.min(self.waves.len() - 1)
+    }
+
+    pub fn get_delay(&self, index: usize) -> f32 {
+        match self.endless.as_ref() {
+            Some(endless) if index >= self.waves.len() => endless.delay,
+            _ => self.waves[self.get_scripted_index(index)].delay,
+        }
+    }
+
+    /// Generated waves also depend on the seed of the run.
+    pub fn get_wave(&self, index: usize, run_seed: u64) -> Cow<'_, WaveDefinition> {
+        match self.endless.as_ref() {
+            Some(endless) if index >= self.waves.len() => {
+                Cow::Owned(endless.generate(index - self.waves.len(), run_seed))
+            }
+            _ => Cow::Borrowed(&self.waves[self.get_scripted_index(index)]),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct WaveSetLoader;
+
+impl AssetLoader for WaveSetLoader {
+    type Asset = WaveSet;
+    type Settings = ();
+    type Error = WaveSetLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<WaveSet, WaveSetLoaderError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            WaveSet::from_slice(&bytes)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["waves.json"]
+    }
+}
+
+pub fn log_wave_set_events_system(mut events: EventReader<AssetEvent<WaveSet>>) {
+    for event in events.read() {
+        if let AssetEvent::Modified { .. } = event {
+            info!("Wave set reloaded");
+        }
+    }
+}
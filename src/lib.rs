@@ -0,0 +1,9 @@
+pub mod core;
+pub mod game;
+pub mod headless;
+pub mod levels;
+pub mod replay;
+pub mod save;
+pub mod utils;
+
+pub use game::GamePlugin;
@@ -0,0 +1,85 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    core::{
+        BeeType, Building, CurrencyStorage, Elite, EnemyType, HiveBuildings, LivingCreature, Shield,
+    },
+    levels::Scenario,
+    utils::StableHasher,
+};
+
+/// Everything that goes into a state checksum.
+#[derive(SystemParam)]
+pub struct ChecksumSource<'w, 's> {
+    units: Query<
+        'w,
+        's,
+        (
+            &'static Transform,
+            &'static LivingCreature,
+            Option<&'static BeeType>,
+            Option<&'static EnemyType>,
+            Option<&'static Shield>,
+            Option<&'static Elite>,
+        ),
+        Or<(With<BeeType>, With<EnemyType>)>,
+    >,
+    buildings: Query<'w, 's, (&'static Building, &'static LivingCreature)>,
+    scenarios: Query<'w, 's, &'static Scenario>,
+    hive_buildings: Res<'w, HiveBuildings>,
+    currency: Res<'w, CurrencyStorage>,
+}
+
+impl<'w, 's> ChecksumSource<'w, 's> {
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+
+        for scenario in self.scenarios.iter() {
+            hasher.write_u64(scenario.wave as u64);
+            hasher.write_u64(scenario.waves_cleared as u64);
+            hasher.write_u64(scenario.bosses_spawned as u64);
+        }
+        hasher.write_debug(&self.hive_buildings.buildings);
+        hasher.write_u64(self.hive_buildings.defender_lvl as u64);
+        hasher.write_u64(self.hive_buildings.worker_lvl as u64);
+        hasher.write_u64(self.hive_buildings.shield_lvl as u64);
+        let mut building_health: Vec<(usize, i32)> = self
+            .buildings
+            .iter()
+            .map(|(building, creature)| (building.index, creature.health))
+            .collect();
+        building_health.sort();
+        for (index, health) in building_health {
+            hasher.write_u64(index as u64);
+            hasher.write_u64(health as u64);
+        }
+        for stored in self.currency.stored {
+            hasher.write_u64(stored);
+        }
+
+        // Query order isn't part of the state, so units are hashed on their own
+        // and combined in sorted order.
+        let mut units: Vec<u64> = self
+            .units
+            .iter()
+            .map(|(transform, creature, bee, enemy, shield, elite)| {
+                let mut unit = StableHasher::new();
+                unit.write_debug(&bee);
+                unit.write_debug(&enemy);
+                unit.write_debug(&elite);
+                unit.write_f32(transform.translation.x);
+                unit.write_f32(transform.translation.y);
+                unit.write_u64(creature.health as u64);
+                unit.write_u64(shield.map_or(0, |shield| shield.amount as u64));
+                unit.finish()
+            })
+            .collect();
+        units.sort_unstable();
+        hasher.write_u64(units.len() as u64);
+        for unit in units {
+            hasher.write_u64(unit);
+        }
+
+        hasher.finish()
+    }
+}
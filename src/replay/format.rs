@@ -0,0 +1,108 @@
+use std::{fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{BuildingCommand, GameSpeed};
+
+/// Bumped every time [`Replay`] changes in a way old replays can't be read.
+pub const REPLAY_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ReplayCommand {
+    Building(BuildingCommand),
+    Speed(GameSpeed),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ReplayEntry {
+    /// [`crate::core::SimulationTick`] the command was applied on.
+    pub tick: u64,
+    pub command: ReplayCommand,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ReplayChecksum {
+    pub tick: u64,
+    pub checksum: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Replay {
+    /// Must stay the first field, it is read on its own before the rest.
+    pub version: u32,
+    pub seed: u64,
+    pub scenario: usize,
+    pub entries: Vec<ReplayEntry>,
+    pub checksums: Vec<ReplayChecksum>,
+    /// Last tick that was recorded.
+    pub final_tick: u64,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Bincode(bincode::Error),
+    IncompatibleVersion { found: u32, expected: u32 },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "could not access replay file: {}", e),
+            ReplayError::Bincode(e) => write!(f, "replay file is corrupted: {}", e),
+            ReplayError::IncompatibleVersion { found, expected } => write!(
+                f,
+                "replay file has format version {}, this build only reads version {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(value: io::Error) -> Self {
+        ReplayError::Io(value)
+    }
+}
+
+impl From<bincode::Error> for ReplayError {
+    fn from(value: bincode::Error) -> Self {
+        ReplayError::Bincode(value)
+    }
+}
+
+impl Replay {
+    pub fn new(seed: u64, scenario: usize) -> Self {
+        Replay {
+            version: REPLAY_FORMAT_VERSION,
+            seed,
+            scenario,
+            entries: vec![],
+            checksums: vec![],
+            final_tick: 0,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), ReplayError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let bytes = bincode::serialize(self)?;
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Replay, ReplayError> {
+        let bytes = fs::read(path)?;
+        let version: u32 = bincode::deserialize(&bytes)?;
+        if version != REPLAY_FORMAT_VERSION {
+            return Err(ReplayError::IncompatibleVersion {
+                found: version,
+                expected: REPLAY_FORMAT_VERSION,
+            });
+        }
+        Ok(bincode::deserialize(&bytes)?)
+    }
+}
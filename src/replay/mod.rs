@@ -0,0 +1,354 @@
+use std::path::PathBuf;
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    core::{
+        building_commands_system, navigation_ready, start_run, AppState, BuildingCommandQueue,
+        GameInfo, GameRng, GameSpeed, HiveDefinition, LogicSet, RunSeedOverride, SimulationSet,
+        SimulationTick,
+    },
+    levels::{ScenarioHives, SelectedScenario, WaveCleared},
+    save::GameLoaded,
+};
+
+mod checksum;
+mod format;
+
+pub use checksum::*;
+pub use format::*;
+
+pub const REPLAY_PATH: &str = "replays/last.bin";
+
+/// Fixed steps between two recorded state checksums.
+pub const CHECKSUM_INTERVAL: u64 = 60;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingReplay>();
+        app.init_resource::<ReplayRecording>();
+        app.add_event::<GameLoaded>();
+
+        app.add_systems(
+            Update,
+            start_pending_replay_system.run_if(in_state(AppState::MainMenu)),
+        );
+
+        app.add_systems(
+            Last,
+            (
+                begin_run_system
+                    .after(start_run)
+                    .run_if(state_changed::<AppState>().and_then(in_state(AppState::InGame))),
+                // Not on startup, a replay can be queued before the first frame
+                end_run_system.run_if(
+                    state_changed::<AppState>()
+                        .and_then(in_state(AppState::MainMenu))
+                        .and_then(not(resource_added::<State<AppState>>())),
+                ),
+                write_replay_system.run_if(in_state(AppState::InGame)),
+            ),
+        );
+
+        app.add_systems(
+            FixedUpdate,
+            (playback_commands_system, record_commands_system)
+                .chain()
+                .before(building_commands_system)
+                .in_set(LogicSet::Commands)
+                .run_if(in_state(AppState::InGame)),
+        );
+        app.add_systems(
+            FixedUpdate,
+            checksum_system
+                .after(SimulationSet::Physics)
+                .run_if(in_state(AppState::InGame).and_then(navigation_ready)),
+        );
+
+        app.add_systems(
+            Update,
+            (
+                playback_speed_system,
+                record_speed_system,
+                stop_on_load_system,
+            )
+                .run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+/// Replay file to play as soon as the main menu is up.
+#[derive(Resource, Default)]
+pub struct PendingReplay(pub Option<PathBuf>);
+
+/// Replay of the running game. `None` while a replay is playing or after a
+/// save was loaded, since the run can't be reproduced from its seed anymore.
+#[derive(Resource, Default)]
+pub struct ReplayRecording(pub Option<Replay>);
+
+/// Feeds a recorded run back into the simulation.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    next_command: usize,
+    next_speed: usize,
+    next_checksum: usize,
+    /// First tick where the replayed state didn't match the recording.
+    pub diverged_at: Option<u64>,
+    pub finished: bool,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayback {
+            replay,
+            next_command: 0,
+            next_speed: 0,
+            next_checksum: 0,
+            diverged_at: None,
+            finished: false,
+        }
+    }
+}
+
+pub fn start_pending_replay_system(
+    mut commands: Commands,
+    mut pending_replay: ResMut<PendingReplay>,
+    mut seed_override: ResMut<RunSeedOverride>,
+    mut selected: ResMut<SelectedScenario>,
+    mut next_state: ResMut<NextState<AppState>>,
+    scenario_hives: Res<ScenarioHives>,
+    hives: Res<Assets<HiveDefinition>>,
+) {
+    // The run would start on the fallback hive otherwise
+    if !scenario_hives.is_loaded(&hives) {
+        return;
+    }
+    let Some(path) = pending_replay.0.take() else {
+        return;
+    };
+
+    let replay = match Replay::read(&path) {
+        Ok(replay) => replay,
+        Err(e) => {
+            warn!("Failed to load replay from {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    info!(
+        "Playing replay {} ({} ticks, seed {})",
+        path.display(),
+        replay.final_tick,
+        replay.seed
+    );
+    seed_override.0 = Some(replay.seed);
+    selected.0 = replay.scenario;
+    commands.insert_resource(ReplayPlayback::new(replay));
+    next_state.set(AppState::InGame);
+}
+
+fn begin_run_system(
+    mut recording: ResMut<ReplayRecording>,
+    playback: Option<Res<ReplayPlayback>>,
+    game_rng: Res<GameRng>,
+    selected: Res<SelectedScenario>,
+) {
+    recording.0 = if playback.is_some() {
+        None
+    } else {
+        Some(Replay::new(game_rng.seed(), selected.0))
+    };
+}
+
+fn end_run_system(
+    mut commands: Commands,
+    mut recording: ResMut<ReplayRecording>,
+    playback: Option<Res<ReplayPlayback>>,
+    mut seed_override: ResMut<RunSeedOverride>,
+) {
+    if let Some(replay) = recording.0.take() {
+        write_replay(&replay);
+    }
+    if playback.is_some() {
+        commands.remove_resource::<ReplayPlayback>();
+        seed_override.0 = None;
+    }
+}
+
+/// Keeps [`REPLAY_PATH`] up to date, so it is there even if the game crashes
+/// later on.
+fn write_replay_system(
+    recording: Res<ReplayRecording>,
+    mut wave_cleared: EventReader<WaveCleared>,
+    mut app_exit: EventReader<AppExit>,
+    game: Res<GameInfo>,
+) {
+    let wave_cleared = wave_cleared.read().last().is_some();
+    let app_exit = app_exit.read().last().is_some();
+    let game_ended = game.is_changed() && game.end;
+    if !(wave_cleared || app_exit || game_ended) {
+        return;
+    }
+    if let Some(replay) = &recording.0 {
+        write_replay(replay);
+    }
+}
+
+fn write_replay(replay: &Replay) {
+    if let Err(e) = replay.write(&PathBuf::from(REPLAY_PATH)) {
+        warn!("Failed to write replay to {}: {}", REPLAY_PATH, e);
+    }
+}
+
+pub fn playback_commands_system(
+    playback: Option<ResMut<ReplayPlayback>>,
+    tick: Res<SimulationTick>,
+    mut queue: ResMut<BuildingCommandQueue>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    let playback = &mut *playback;
+
+    // Only recorded commands drive a replay
+    queue.0.clear();
+    while let Some(entry) = playback.replay.entries.get(playback.next_command) {
+        if entry.tick > tick.0 {
+            break;
+        }
+        if let ReplayCommand::Building(command) = entry.command {
+            queue.0.push(command);
+        }
+        playback.next_command += 1;
+    }
+}
+
+pub fn record_commands_system(
+    mut recording: ResMut<ReplayRecording>,
+    tick: Res<SimulationTick>,
+    queue: Res<BuildingCommandQueue>,
+) {
+    let Some(replay) = recording.0.as_mut() else {
+        return;
+    };
+    for command in queue.0.iter() {
+        replay.entries.push(ReplayEntry {
+            tick: tick.0,
+            command: ReplayCommand::Building(*command),
+        });
+    }
+}
+
+pub fn checksum_system(
+    source: ChecksumSource,
+    tick: Res<SimulationTick>,
+    mut recording: ResMut<ReplayRecording>,
+    playback: Option<ResMut<ReplayPlayback>>,
+    mut game_speed: ResMut<GameSpeed>,
+) {
+    if let Some(replay) = recording.0.as_mut() {
+        replay.final_tick = tick.0;
+        if tick.0.is_multiple_of(CHECKSUM_INTERVAL) {
+            replay.checksums.push(ReplayChecksum {
+                tick: tick.0,
+                checksum: source.checksum(),
+            });
+        }
+    }
+
+    let Some(mut playback) = playback else {
+        return;
+    };
+    let playback = &mut *playback;
+    if playback.finished {
+        return;
+    }
+
+    while let Some(expected) = playback.replay.checksums.get(playback.next_checksum) {
+        if expected.tick > tick.0 {
+            break;
+        }
+        playback.next_checksum += 1;
+        if expected.tick < tick.0 || playback.diverged_at.is_some() {
+            continue;
+        }
+        let checksum = source.checksum();
+        if checksum != expected.checksum {
+            warn!(
+                "Replay diverged at tick {}: recorded checksum {:016x}, replayed {:016x}",
+                tick.0, expected.checksum, checksum
+            );
+            playback.diverged_at = Some(tick.0);
+        }
+    }
+
+    if tick.0 >= playback.replay.final_tick {
+        playback.finished = true;
+        *game_speed = GameSpeed::Paused;
+        match playback.diverged_at {
+            Some(diverged_at) => warn!(
+                "Replay finished at tick {}, diverged at tick {}",
+                tick.0, diverged_at
+            ),
+            None => info!("Replay finished at tick {} without divergence", tick.0),
+        }
+    }
+}
+
+fn record_speed_system(
+    mut recording: ResMut<ReplayRecording>,
+    tick: Res<SimulationTick>,
+    game_speed: Res<GameSpeed>,
+) {
+    if !game_speed.is_changed() {
+        return;
+    }
+    if let Some(replay) = recording.0.as_mut() {
+        replay.entries.push(ReplayEntry {
+            tick: tick.0,
+            command: ReplayCommand::Speed(*game_speed),
+        });
+    }
+}
+
+fn playback_speed_system(
+    playback: Option<ResMut<ReplayPlayback>>,
+    tick: Res<SimulationTick>,
+    mut game_speed: ResMut<GameSpeed>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    let playback = &mut *playback;
+
+    while let Some(entry) = playback.replay.entries.get(playback.next_speed) {
+        if entry.tick > tick.0 {
+            break;
+        }
+        if let ReplayCommand::Speed(speed) = entry.command {
+            *game_speed = speed;
+        }
+        playback.next_speed += 1;
+    }
+}
+
+fn stop_on_load_system(
+    mut commands: Commands,
+    mut game_loaded: EventReader<GameLoaded>,
+    mut recording: ResMut<ReplayRecording>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if game_loaded.read().last().is_none() {
+        return;
+    }
+    if recording.0.take().is_some() {
+        info!("Replay recording stopped, a save was loaded");
+    }
+    if playback.is_some() {
+        info!("Replay stopped, a save was loaded");
+        commands.remove_resource::<ReplayPlayback>();
+    }
+}
@@ -0,0 +1,170 @@
+use std::{fmt, fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    BeeType, Building, CurrencyGainPerMinute, CurrencyStorage, Elite, EnemyType, HiveAlarm,
+    HiveBuildings, LivingCreature, RigidBody, Shield, ShieldCooldown, SimulationTick,
+    SpawnCounter, SpawnOrder, UniversalBehaviour, RNG_STREAMS_NUM,
+};
+
+/// Bumped every time [`SaveGame`] changes in a way old saves can't be read.
+pub const SAVE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SavedUnitKind {
+    Bee(BeeType),
+    Enemy { enemy: EnemyType, boss: bool },
+}
+
+/// Units are saved as indices into [`SaveGame::units`], buildings by their
+/// slot index.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SavedNavigationTarget {
+    None,
+    Position(Vec2),
+    Unit(usize, f32),
+    Building(usize, f32),
+}
+
+/// State of a [`UniversalBehaviour`], the rest of it comes from the unit stats.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SavedBehaviour {
+    pub time_since_last_refresh: f32,
+    pub retreating: bool,
+    pub heal_progress: f32,
+}
+
+impl SavedBehaviour {
+    pub fn apply(&self, behaviour: &mut UniversalBehaviour) {
+        behaviour.time_since_last_refresh = self.time_since_last_refresh;
+        behaviour.retreating = self.retreating;
+        behaviour.heal_progress = self.heal_progress;
+    }
+}
+
+impl From<&UniversalBehaviour> for SavedBehaviour {
+    fn from(value: &UniversalBehaviour) -> Self {
+        SavedBehaviour {
+            time_since_last_refresh: value.time_since_last_refresh,
+            retreating: value.retreating,
+            heal_progress: value.heal_progress,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedUnit {
+    pub kind: SavedUnitKind,
+    pub translation: Vec3,
+    pub creature: LivingCreature,
+    pub rigid_body: RigidBody,
+    pub target: SavedNavigationTarget,
+    pub gain: Option<CurrencyGainPerMinute>,
+    pub shield: Option<Shield>,
+    pub elite: Option<Elite>,
+    pub behaviour: Option<SavedBehaviour>,
+    pub spawn_order: Option<SpawnOrder>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedBuilding {
+    pub building: Building,
+    /// Health of the building, empty lots have none.
+    pub creature: Option<LivingCreature>,
+    pub spawn_order: Option<SpawnOrder>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SavedScenario {
+    pub index: usize,
+    pub time_elapsed: f32,
+    pub wave: usize,
+    pub wave_active: bool,
+    pub waves_cleared: usize,
+    pub bosses_spawned: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SaveGame {
+    /// Must stay the first field, it is read on its own before the rest.
+    pub version: u32,
+    pub seed: u64,
+    /// See [`crate::core::GameRng::word_positions`].
+    pub rng_word_positions: [u128; RNG_STREAMS_NUM],
+    pub spawn_counter: SpawnCounter,
+    pub tick: SimulationTick,
+    pub shield_cooldown: ShieldCooldown,
+    pub hive_alarm: HiveAlarm,
+    pub scenario: SavedScenario,
+    pub hive_buildings: HiveBuildings,
+    pub currency: CurrencyStorage,
+    /// Building entities with their order queues.
+    pub buildings: Vec<SavedBuilding>,
+    pub units: Vec<SavedUnit>,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Bincode(bincode::Error),
+    IncompatibleVersion { found: u32, expected: u32 },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "could not access save file: {}", e),
+            SaveError::Bincode(e) => write!(f, "save file is corrupted: {}", e),
+            SaveError::IncompatibleVersion { found, expected } => write!(
+                f,
+                "save file has format version {}, this build only reads version {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(value: io::Error) -> Self {
+        SaveError::Io(value)
+    }
+}
+
+impl From<bincode::Error> for SaveError {
+    fn from(value: bincode::Error) -> Self {
+        SaveError::Bincode(value)
+    }
+}
+
+impl SaveGame {
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let bytes = bincode::serialize(self)?;
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<SaveGame, SaveError> {
+        let bytes = fs::read(path)?;
+        Self::from_slice(&bytes)
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<SaveGame, SaveError> {
+        // Check the version first, so an old save gives a clear error
+        // instead of whatever the decoder trips over.
+        let version: u32 = bincode::deserialize(bytes)?;
+        if version != SAVE_FORMAT_VERSION {
+            return Err(SaveError::IncompatibleVersion {
+                found: version,
+                expected: SAVE_FORMAT_VERSION,
+            });
+        }
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
@@ -0,0 +1,417 @@
+use std::{fs, path::PathBuf};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    core::{
+        spawn_building, AppState, BeeBundle, BeeType, Building, BuildingCommandQueue,
+        CurrencyGainPerMinute, CurrencyStorage, Elite, EnemyType, GameInfo, GameRng, HiveBuildings,
+        LivingCreature, NavigationTarget, RigidBody, RunSeedText, Shield, UnitRegistry,
+        UniversalBehaviour, get_spawn_ordered, HiveAlarm, ShieldCooldown, SimulationTick,
+        SpawnCounter, SpawnOrder,
+    },
+    levels::{spawn_enemy_at, Boss, Scenario, ScenarioRegistry, SelectedScenario, WaveCleared},
+};
+
+mod format;
+
+pub use format::*;
+
+pub const QUICKSAVE_PATH: &str = "saves/quicksave.bin";
+pub const AUTOSAVE_PATH: &str = "saves/autosave.bin";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveRequest>();
+        app.add_event::<GameLoaded>();
+        app.init_resource::<PendingLoad>();
+
+        app.add_systems(
+            Update,
+            (
+                quicksave_keys_system,
+                autosave_system,
+                save_game_system,
+                load_game_system,
+                remove_finished_autosave_system,
+            )
+                .chain()
+                .run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+/// Asks [`save_game_system`] to write the running game to the given path.
+#[derive(Event)]
+pub struct SaveRequest(pub PathBuf);
+
+/// Sent after a save file replaced the running game.
+#[derive(Event)]
+pub struct GameLoaded;
+
+/// Save file to restore as soon as a game is running.
+#[derive(Resource, Default)]
+pub struct PendingLoad(pub Option<PathBuf>);
+
+type UnitQueryData<'a> = (
+    Entity,
+    &'a Transform,
+    &'a LivingCreature,
+    &'a RigidBody,
+    &'a NavigationTarget,
+    Option<&'a BeeType>,
+    Option<&'a EnemyType>,
+    Option<&'a Boss>,
+    Option<&'a CurrencyGainPerMinute>,
+    Option<&'a Shield>,
+    Option<&'a Elite>,
+    Option<&'a UniversalBehaviour>,
+    Option<&'a SpawnOrder>,
+);
+
+/// Everything that goes into a [`SaveGame`].
+#[derive(SystemParam)]
+pub struct SaveGameSource<'w, 's> {
+    units: Query<'w, 's, UnitQueryData<'static>, Or<(With<BeeType>, With<EnemyType>)>>,
+    buildings: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Building,
+            Option<&'static LivingCreature>,
+            Option<&'static SpawnOrder>,
+        ),
+    >,
+    scenarios: Query<'w, 's, &'static Scenario>,
+    hive_buildings: Res<'w, HiveBuildings>,
+    currency: Res<'w, CurrencyStorage>,
+    game_rng: Res<'w, GameRng>,
+    spawn_counter: Res<'w, SpawnCounter>,
+    tick: Res<'w, SimulationTick>,
+    shield_cooldown: Res<'w, ShieldCooldown>,
+    hive_alarm: Res<'w, HiveAlarm>,
+}
+
+impl<'w, 's> SaveGameSource<'w, 's> {
+    pub fn capture(&self) -> Option<SaveGame> {
+        let scenario = self.scenarios.iter().next()?;
+
+        // Saved in spawn order, so they are spawned back in it
+        let order = get_spawn_ordered(self.units.iter().map(|unit| (unit.0, unit.12)));
+        let indices: bevy::utils::HashMap<Entity, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (*e, i))
+            .collect();
+        let building_indices: bevy::utils::HashMap<Entity, usize> = self
+            .buildings
+            .iter()
+            .map(|(e, building, _, _)| (e, building.index))
+            .collect();
+
+        let mut units = vec![];
+        for (
+            _,
+            transform,
+            creature,
+            rb,
+            target,
+            bee,
+            enemy,
+            boss,
+            gain,
+            shield,
+            elite,
+            behaviour,
+            spawn_order,
+        ) in self.units.iter_many(&order)
+        {
+            let kind = match (bee, enemy) {
+                (Some(bee), _) => SavedUnitKind::Bee(*bee),
+                (None, Some(enemy)) => SavedUnitKind::Enemy {
+                    enemy: *enemy,
+                    boss: boss.is_some(),
+                },
+                (None, None) => continue,
+            };
+            let target = match *target {
+                NavigationTarget::None => SavedNavigationTarget::None,
+                NavigationTarget::Position(position) => SavedNavigationTarget::Position(position),
+                NavigationTarget::Entity(e, range) => {
+                    match (indices.get(&e), building_indices.get(&e)) {
+                        (Some(index), _) => SavedNavigationTarget::Unit(*index, range),
+                        (None, Some(index)) => SavedNavigationTarget::Building(*index, range),
+                        (None, None) => SavedNavigationTarget::None,
+                    }
+                }
+            };
+            units.push(SavedUnit {
+                kind,
+                translation: transform.translation,
+                creature: creature.clone(),
+                rigid_body: rb.clone(),
+                target,
+                gain: gain.cloned(),
+                shield: shield.cloned(),
+                elite: elite.cloned(),
+                behaviour: behaviour.map(SavedBehaviour::from),
+                spawn_order: spawn_order.copied(),
+            });
+        }
+
+        let mut buildings: Vec<SavedBuilding> = self
+            .buildings
+            .iter()
+            .map(|(_, building, creature, spawn_order)| SavedBuilding {
+                building: building.clone(),
+                creature: creature.cloned(),
+                spawn_order: spawn_order.copied(),
+            })
+            .collect();
+        buildings.sort_by_key(|b| b.building.index);
+
+        Some(SaveGame {
+            version: SAVE_FORMAT_VERSION,
+            seed: self.game_rng.seed(),
+            rng_word_positions: self.game_rng.word_positions(),
+            spawn_counter: *self.spawn_counter,
+            tick: *self.tick,
+            shield_cooldown: *self.shield_cooldown,
+            hive_alarm: self.hive_alarm.clone(),
+            scenario: SavedScenario {
+                index: scenario.index,
+                time_elapsed: scenario.time_elapsed,
+                wave: scenario.wave,
+                wave_active: scenario.wave_active,
+                waves_cleared: scenario.waves_cleared,
+                bosses_spawned: scenario.bosses_spawned,
+            },
+            hive_buildings: self.hive_buildings.clone(),
+            currency: self.currency.clone(),
+            buildings,
+            units,
+        })
+    }
+}
+
+/// `F5` saves to [`QUICKSAVE_PATH`], `F9` loads it back.
+pub fn quicksave_keys_system(
+    keys: Res<Input<KeyCode>>,
+    mut save_requests: EventWriter<SaveRequest>,
+    mut pending_load: ResMut<PendingLoad>,
+) {
+    if keys.just_pressed(KeyCode::F5) {
+        save_requests.send(SaveRequest(PathBuf::from(QUICKSAVE_PATH)));
+    }
+    if keys.just_pressed(KeyCode::F9) {
+        pending_load.0 = Some(PathBuf::from(QUICKSAVE_PATH));
+    }
+}
+
+pub fn has_autosave() -> bool {
+    PathBuf::from(AUTOSAVE_PATH).is_file()
+}
+
+pub fn autosave_system(
+    mut wave_cleared: EventReader<WaveCleared>,
+    mut save_requests: EventWriter<SaveRequest>,
+) {
+    if wave_cleared.read().last().is_some() {
+        save_requests.send(SaveRequest(PathBuf::from(AUTOSAVE_PATH)));
+    }
+}
+
+/// A finished run can't be continued.
+pub fn remove_finished_autosave_system(game: Res<GameInfo>) {
+    if game.is_changed() && game.end && has_autosave() {
+        if let Err(e) = fs::remove_file(AUTOSAVE_PATH) {
+            warn!("Failed to remove autosave: {}", e);
+        }
+    }
+}
+
+pub fn save_game_system(
+    mut save_requests: EventReader<SaveRequest>,
+    source: SaveGameSource,
+    game: Res<GameInfo>,
+) {
+    for request in save_requests.read() {
+        if game.end {
+            info!("Game is over, nothing to save");
+            continue;
+        }
+        let Some(save) = source.capture() else {
+            continue;
+        };
+        match save.write(&request.0) {
+            Ok(()) => info!("Game saved to {}", request.0.display()),
+            Err(e) => warn!("Failed to save game to {}: {}", request.0.display(), e),
+        }
+    }
+}
+
+pub fn load_game_system(
+    mut commands: Commands,
+    mut pending_load: ResMut<PendingLoad>,
+    mut scenarios: Query<&mut Scenario>,
+    units: Query<Entity, Or<(With<BeeType>, With<EnemyType>)>>,
+    buildings: Query<Entity, With<Building>>,
+    mut seed_text: Query<&mut Text, With<RunSeedText>>,
+    mut hive_buildings: ResMut<HiveBuildings>,
+    mut command_queue: ResMut<BuildingCommandQueue>,
+    mut currency: ResMut<CurrencyStorage>,
+    (mut game_rng, mut tick, mut shield_cooldown, mut hive_alarm): (
+        ResMut<GameRng>,
+        ResMut<SimulationTick>,
+        ResMut<ShieldCooldown>,
+        ResMut<HiveAlarm>,
+    ),
+    mut game: ResMut<GameInfo>,
+    mut selected: ResMut<SelectedScenario>,
+    registry: Res<ScenarioRegistry>,
+    asset_server: Res<AssetServer>,
+    (mut meshes, unit_registry): (ResMut<Assets<Mesh>>, Res<UnitRegistry>),
+    mut game_loaded: EventWriter<GameLoaded>,
+) {
+    // Wait for the game to be set up, the save is applied on top of it
+    let Ok(mut scenario) = scenarios.get_single_mut() else {
+        return;
+    };
+    let Some(path) = pending_load.0.take() else {
+        return;
+    };
+
+    let save = match SaveGame::read(&path) {
+        Ok(save) => save,
+        Err(e) => {
+            warn!("Failed to load game from {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    for e in units.iter().chain(buildings.iter()) {
+        commands.entity(e).despawn();
+    }
+
+    *hive_buildings = save.hive_buildings;
+    command_queue.0.clear();
+    *currency = save.currency;
+    *game_rng = GameRng::restore(save.seed, save.rng_word_positions);
+    *tick = save.tick;
+    *shield_cooldown = save.shield_cooldown;
+    *hive_alarm = save.hive_alarm;
+    *game = GameInfo::default();
+    for mut text in seed_text.iter_mut() {
+        text.sections[0].value = format!("Seed: {}", save.seed);
+    }
+
+    selected.0 = save.scenario.index;
+    *scenario = Scenario {
+        index: save.scenario.index,
+        time_elapsed: save.scenario.time_elapsed,
+        wave: save.scenario.wave,
+        waves: asset_server.load(&registry.get(save.scenario.index).waves),
+        wave_active: save.scenario.wave_active,
+        waves_cleared: save.scenario.waves_cleared,
+        bosses_spawned: save.scenario.bosses_spawned,
+    };
+
+    let mut building_entities = bevy::utils::HashMap::new();
+    for saved in save.buildings {
+        let index = saved.building.index;
+        let position = hive_buildings.get_building_position(index);
+        let e = spawn_building(&mut commands, saved.building, position);
+        building_entities.insert(index, e);
+        if let Some(creature) = saved.creature {
+            commands.entity(e).insert(creature);
+        }
+        if let Some(spawn_order) = saved.spawn_order {
+            commands.entity(e).insert(spawn_order);
+        }
+    }
+
+    let entities: Vec<Entity> = save
+        .units
+        .iter()
+        .map(|unit| spawn_unit(&mut commands, &mut meshes, &unit_registry, unit))
+        .collect();
+
+    // Targets point at other units and buildings, so they can only be restored
+    // once all of them exist
+    for (unit, e) in save.units.iter().zip(entities.iter()) {
+        let target = match unit.target {
+            SavedNavigationTarget::None => NavigationTarget::None,
+            SavedNavigationTarget::Position(position) => NavigationTarget::Position(position),
+            SavedNavigationTarget::Unit(index, range) => entities
+                .get(index)
+                .map_or(NavigationTarget::None, |target| NavigationTarget::Entity(*target, range)),
+            SavedNavigationTarget::Building(index, range) => building_entities
+                .get(&index)
+                .map_or(NavigationTarget::None, |target| NavigationTarget::Entity(*target, range)),
+        };
+        commands.entity(*e).insert(target);
+    }
+
+    // Spawning the saved entities handed out new spawn orders, they were
+    // replaced by the saved ones above
+    let spawn_counter = save.spawn_counter;
+    commands.add(move |world: &mut World| world.insert_resource(spawn_counter));
+
+    game_loaded.send(GameLoaded);
+    info!("Game loaded from {}", path.display());
+}
+
+fn spawn_unit(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    unit_registry: &UnitRegistry,
+    unit: &SavedUnit,
+) -> Entity {
+    let e = match unit.kind {
+        SavedUnitKind::Bee(bee) => {
+            let mut bundle = BeeBundle::new(bee, unit.translation, unit_registry);
+            bundle.creature = unit.creature.clone();
+            bundle.rigid_body = unit.rigid_body.clone();
+            if let Some(gain) = &unit.gain {
+                bundle.gain = gain.clone();
+            }
+            if let Some(behaviour) = &unit.behaviour {
+                behaviour.apply(&mut bundle.behaviour);
+            }
+            commands.spawn(bundle).id()
+        }
+        SavedUnitKind::Enemy { enemy, boss } => {
+            let e = spawn_enemy_at(
+                commands,
+                enemy,
+                meshes,
+                unit_registry,
+                unit.translation,
+                unit.creature.clone(),
+            );
+            commands.entity(e).insert(unit.rigid_body.clone());
+            if let Some(behaviour) = &unit.behaviour {
+                let mut restored = unit_registry.get(enemy).get_behaviour();
+                behaviour.apply(&mut restored);
+                commands.entity(e).insert(restored);
+            }
+            if boss {
+                commands.entity(e).insert(Boss);
+            }
+            e
+        }
+    };
+    if let Some(shield) = &unit.shield {
+        commands.entity(e).insert(shield.clone());
+    }
+    if let Some(elite) = &unit.elite {
+        commands.entity(e).insert(elite.clone());
+    }
+    if let Some(spawn_order) = unit.spawn_order {
+        commands.entity(e).insert(spawn_order);
+    }
+    e
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::Vec2;
+use serde::Deserialize;
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug, Deserialize)]
 pub enum Direction {
     Right,
     Top,
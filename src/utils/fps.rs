@@ -38,7 +38,7 @@ impl Default for FpsTimer {
 pub struct FpsText;
 
 fn update(
-    time: Res<Time>,
+    time: Res<Time<Real>>,
     diagnostics: Res<DiagnosticsStore>,
     mut state: ResMut<FpsTimer>,
     mut text_query: Query<&mut Text, With<FpsText>>,
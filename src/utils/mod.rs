@@ -1,9 +1,11 @@
 mod direction;
 mod fps;
+mod hash;
 mod vec_helpers;
 
 pub use direction::*;
 pub use fps::*;
+pub use hash::*;
 pub use vec_helpers::*;
 
 pub fn is_local_build() -> bool {
@@ -0,0 +1,156 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_jam_4::core::{
+    alarm_system, spatial_grid_system, universal_behaviour_system, Alarm, BeeType, EnemyType,
+    Faction, GameRng, HiveAlarm, HiveBuildings, HiveDefinition, HiveMap, LivingCreature,
+    NavigationResult, NavigationTarget, SpatialGrid, UniversalBehaviour, ALARM_DURATION,
+    ALARM_RADIUS,
+};
+use common::{run_for, stats, test_app};
+
+fn attacked(creature: LivingCreature) -> LivingCreature {
+    LivingCreature {
+        time_since_last_damage_taken: 0.0,
+        ..creature
+    }
+}
+
+fn alarm_app() -> App {
+    let mut app = test_app();
+    app.init_resource::<HiveAlarm>();
+    app.add_systems(Update, alarm_system);
+    app
+}
+
+fn behaviour_app() -> App {
+    let mut app = test_app();
+    app.init_resource::<HiveAlarm>();
+    app.init_resource::<HiveBuildings>();
+    app.init_resource::<GameRng>();
+    app.insert_resource(HiveMap::open(&HiveDefinition::default()));
+    app.init_resource::<SpatialGrid>();
+    app.add_systems(
+        Update,
+        (spatial_grid_system, universal_behaviour_system).chain(),
+    );
+    app
+}
+
+fn spawn_bee(app: &mut App, bee: BeeType, position: Vec2) -> Entity {
+    app.world
+        .spawn((
+            stats(bee).get_behaviour(),
+            stats(bee).get_creature(),
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            Faction::Bees,
+            stats(bee).get_rigid_body(),
+            NavigationTarget::None,
+            NavigationResult::default(),
+        ))
+        .id()
+}
+
+#[test]
+fn attacked_bee_raises_alarm() {
+    let mut app = alarm_app();
+    app.world.spawn((
+        attacked(stats(BeeType::Regular).get_creature()),
+        TransformBundle::from_transform(Transform::from_xyz(30.0, 40.0, 0.0)),
+        Faction::Bees,
+    ));
+
+    app.update();
+
+    let hive_alarm = app.world.resource::<HiveAlarm>();
+    assert!(hive_alarm.is_raised());
+    assert_eq!(hive_alarm.alarms[0].position, Vec2::new(30.0, 40.0));
+}
+
+#[test]
+fn attacked_enemy_raises_no_alarm() {
+    let mut app = alarm_app();
+    app.world.spawn((
+        attacked(stats(EnemyType::Wasp(0)).get_creature()),
+        TransformBundle::default(),
+        Faction::Enemies,
+    ));
+
+    app.update();
+
+    assert!(!app.world.resource::<HiveAlarm>().is_raised());
+}
+
+#[test]
+fn alarm_shrinks_and_expires() {
+    let mut app = alarm_app();
+    app.world.resource_mut::<HiveAlarm>().raise(Vec2::ZERO);
+
+    run_for(&mut app, ALARM_DURATION / 2.0);
+    let radius = app.world.resource::<HiveAlarm>().alarms[0].get_radius();
+    assert!(radius < ALARM_RADIUS && radius > 0.0);
+
+    run_for(&mut app, ALARM_DURATION / 2.0 + 0.5);
+    assert!(!app.world.resource::<HiveAlarm>().is_raised());
+}
+
+#[test]
+fn nearby_attacks_refresh_the_same_alarm() {
+    let mut hive_alarm = HiveAlarm::default();
+    hive_alarm.alarms.push(Alarm {
+        position: Vec2::ZERO,
+        age: 3.0,
+    });
+
+    hive_alarm.raise(Vec2::new(10.0, 0.0));
+    hive_alarm.raise(Vec2::new(400.0, 0.0));
+
+    assert_eq!(hive_alarm.alarms.len(), 2);
+    assert_eq!(hive_alarm.alarms[0].age, 0.0);
+}
+
+#[test]
+fn alarmed_defenders_engage_from_further_away() {
+    let mut app = behaviour_app();
+    let defender = spawn_bee(&mut app, BeeType::Defender(0), Vec2::ZERO);
+    let enemy = app
+        .world
+        .spawn((
+            stats(EnemyType::Wasp(0)).get_creature(),
+            TransformBundle::from_transform(Transform::from_xyz(300.0, 0.0, 0.0)),
+            stats(EnemyType::Wasp(0)).get_rigid_body(),
+            Faction::Enemies,
+        ))
+        .id();
+
+    app.update();
+    assert!(!matches!(
+        app.world.get::<NavigationTarget>(defender).unwrap(),
+        NavigationTarget::Entity(..)
+    ));
+
+    app.world
+        .resource_mut::<HiveAlarm>()
+        .raise(Vec2::new(200.0, 0.0));
+    run_for(&mut app, 0.6);
+
+    assert!(matches!(
+        app.world.get::<NavigationTarget>(defender).unwrap(),
+        NavigationTarget::Entity(e, _) if *e == enemy
+    ));
+}
+
+#[test]
+fn alarmed_workers_retreat() {
+    let mut app = behaviour_app();
+    let worker = spawn_bee(&mut app, BeeType::Worker(0), Vec2::new(150.0, 0.0));
+    let regular = spawn_bee(&mut app, BeeType::Regular, Vec2::new(150.0, 20.0));
+    app.world
+        .resource_mut::<HiveAlarm>()
+        .raise(Vec2::new(160.0, 0.0));
+
+    app.update();
+
+    assert!(app.world.get::<UniversalBehaviour>(worker).unwrap().retreating);
+    assert!(!app.world.get::<UniversalBehaviour>(regular).unwrap().retreating);
+}
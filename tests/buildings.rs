@@ -0,0 +1,464 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_jam_4::{
+    core::{
+        building_commands_system, building_destroyed_system, buildings_system, fight_system,
+        living_creature_system, queue_building_commands_system, BeeBundle, BeeType, Building,
+        BuildRequested, BuildingCommand, BuildingCommandQueue, BuildingCommandRejected,
+        BuildingCommandSucceeded, BuildingKind, CurrencyStorage, DestroyRequested, EnemyType,
+        Faction, GameInfo, GameRng, HiveBuildings, LivingCreature, NavigationTarget,
+        OrderRequested, RejectReason, UnitRegistry, UpgradeRequested,
+    },
+    levels::{scenario_conditions_system, Scenario, ScenarioRegistry},
+};
+use common::{run_for, stats, test_app};
+
+fn buildings_app(stored: [u64; 3]) -> App {
+    let mut app = test_app();
+    app.init_resource::<HiveBuildings>();
+    app.init_resource::<GameRng>();
+    app.init_resource::<UnitRegistry>();
+    app.insert_resource(CurrencyStorage {
+        stored,
+        ..default()
+    });
+    app.init_resource::<BuildingCommandQueue>();
+    app.add_event::<BuildingCommand>();
+    app.add_event::<BuildRequested>();
+    app.add_event::<UpgradeRequested>();
+    app.add_event::<DestroyRequested>();
+    app.add_event::<OrderRequested>();
+    app.add_event::<BuildingCommandSucceeded>();
+    app.add_event::<BuildingCommandRejected>();
+    app.add_systems(PreUpdate, queue_building_commands_system);
+    app.add_systems(Update, (building_commands_system, buildings_system).chain());
+    app
+}
+
+fn spawn(app: &mut App, kind: BuildingKind, index: usize) -> Entity {
+    let mut hive_buildings = app.world.resource_mut::<HiveBuildings>();
+    hive_buildings.buildings[index] = kind;
+    let position = hive_buildings.get_building_position(index).extend(-5.0);
+    app.world
+        .spawn((
+            Building::new(kind, index),
+            TransformBundle::from_transform(Transform::from_translation(position)),
+        ))
+        .id()
+}
+
+fn rejections(app: &App) -> Vec<RejectReason> {
+    app.world
+        .resource::<Events<BuildingCommandRejected>>()
+        .iter_current_update_events()
+        .map(|r| r.reason)
+        .collect()
+}
+
+fn count_bees(app: &mut App, bee: BeeType) -> usize {
+    app.world
+        .query::<&BeeType>()
+        .iter(&app.world)
+        .filter(|b| **b == bee)
+        .count()
+}
+
+#[test]
+fn build_order_spends_currency() {
+    let mut app = buildings_app([50, 10, 0]);
+    app.world.send_event(BuildingCommand::Build {
+        index: 4,
+        kind: BuildingKind::Workshop,
+    });
+
+    app.update();
+
+    assert_eq!(app.world.resource::<HiveBuildings>().buildings[4], BuildingKind::Workshop);
+    assert_eq!(app.world.resource::<CurrencyStorage>().stored, [42, 8, 0]);
+    let succeeded: Vec<_> = app
+        .world
+        .resource::<Events<BuildingCommandSucceeded>>()
+        .iter_current_update_events()
+        .map(|s| s.0)
+        .collect();
+    assert_eq!(
+        succeeded,
+        [BuildingCommand::Build {
+            index: 4,
+            kind: BuildingKind::Workshop
+        }]
+    );
+}
+
+#[test]
+fn build_order_needs_currency() {
+    let mut app = buildings_app([0, 0, 0]);
+    app.world.send_event(BuildingCommand::Build {
+        index: 6,
+        kind: BuildingKind::Armory,
+    });
+
+    app.update();
+
+    assert_eq!(app.world.resource::<HiveBuildings>().buildings[6], BuildingKind::None);
+    assert_eq!(app.world.resource::<CurrencyStorage>().stored, [0, 0, 0]);
+    assert_eq!(rejections(&app), [RejectReason::NotEnoughCurrency]);
+}
+
+#[test]
+fn requests_in_one_frame_are_all_applied() {
+    let mut app = buildings_app([50, 10, 0]);
+    app.world.send_event(BuildingCommand::Build {
+        index: 4,
+        kind: BuildingKind::Workshop,
+    });
+    app.world.send_event(BuildingCommand::Build {
+        index: 6,
+        kind: BuildingKind::Armory,
+    });
+
+    app.update();
+
+    let hive_buildings = app.world.resource::<HiveBuildings>();
+    assert_eq!(hive_buildings.buildings[4], BuildingKind::Workshop);
+    assert_eq!(hive_buildings.buildings[6], BuildingKind::Armory);
+    assert_eq!(app.world.resource::<CurrencyStorage>().stored, [26, 6, 0]);
+}
+
+#[test]
+fn requests_in_one_frame_keep_their_order() {
+    let mut app = buildings_app([50, 10, 0]);
+    spawn(&mut app, BuildingKind::Workshop, 4);
+    app.world.send_event(BuildingCommand::Destroy { index: 4 });
+    app.world.send_event(BuildingCommand::Build {
+        index: 4,
+        kind: BuildingKind::Armory,
+    });
+
+    app.update();
+
+    assert_eq!(
+        app.world.resource::<HiveBuildings>().buildings[4],
+        BuildingKind::Armory
+    );
+    assert!(rejections(&app).is_empty());
+}
+
+#[test]
+fn order_right_after_build_goes_to_the_new_building() {
+    let mut app = buildings_app([50, 10, 0]);
+    app.world.send_event(BuildingCommand::Build {
+        index: 4,
+        kind: BuildingKind::Workshop,
+    });
+    app.world.send_event(BuildingCommand::Order { index: 4 });
+
+    app.update();
+
+    assert!(rejections(&app).is_empty());
+    assert_eq!(app.world.resource::<CurrencyStorage>().stored, [36, 7, 0]);
+    let workshop = app
+        .world
+        .query::<&Building>()
+        .iter(&app.world)
+        .find(|b| b.index == 4)
+        .cloned()
+        .expect("the workshop is spawned");
+    assert_eq!(workshop.kind, BuildingKind::Workshop);
+    assert_eq!(workshop.orders_count, 1);
+}
+
+#[test]
+fn typed_requests_are_queued_like_commands() {
+    let mut app = buildings_app([50, 10, 0]);
+    // Sent in the wrong order, a build is still queued before an order
+    app.world.send_event(OrderRequested { index: 4 });
+    app.world.send_event(BuildRequested {
+        index: 4,
+        kind: BuildingKind::Workshop,
+    });
+
+    app.update();
+
+    assert!(rejections(&app).is_empty());
+    let workshop = app
+        .world
+        .query::<&Building>()
+        .iter(&app.world)
+        .find(|b| b.index == 4)
+        .cloned()
+        .expect("the workshop is spawned");
+    assert_eq!(workshop.kind, BuildingKind::Workshop);
+    assert_eq!(workshop.orders_count, 1);
+
+    app.world.send_event(DestroyRequested { index: 4 });
+    app.update();
+
+    assert_eq!(app.world.resource::<HiveBuildings>().buildings[4], BuildingKind::None);
+}
+
+#[test]
+fn destroying_a_storage_lowers_max_honey() {
+    let mut app = buildings_app([100, 20, 0]);
+    app.world.send_event(BuildingCommand::Build {
+        index: 4,
+        kind: BuildingKind::Storage,
+    });
+    app.update();
+    assert_eq!(app.world.resource::<HiveBuildings>().storages, 1);
+    assert_eq!(app.world.resource::<CurrencyStorage>().max_stored[0], 200);
+
+    app.world.resource_mut::<CurrencyStorage>().stored[0] = 150;
+    app.world.send_event(BuildingCommand::Destroy { index: 4 });
+    app.update();
+
+    assert!(rejections(&app).is_empty());
+    assert_eq!(app.world.resource::<HiveBuildings>().storages, 0);
+    let currency = app.world.resource::<CurrencyStorage>();
+    assert_eq!(currency.max_stored[0], 100);
+    assert_eq!(currency.stored[0], 100);
+}
+
+#[test]
+fn build_on_occupied_slot_is_rejected() {
+    let mut app = buildings_app([50, 10, 0]);
+    spawn(&mut app, BuildingKind::Nexus, 8);
+    app.world.send_event(BuildingCommand::Build {
+        index: 8,
+        kind: BuildingKind::Workshop,
+    });
+
+    app.update();
+
+    assert_eq!(app.world.resource::<HiveBuildings>().buildings[8], BuildingKind::Nexus);
+    assert_eq!(rejections(&app), [RejectReason::SlotOccupied]);
+}
+
+#[test]
+fn nexus_cannot_be_destroyed() {
+    let mut app = buildings_app([0, 0, 0]);
+    spawn(&mut app, BuildingKind::Nexus, 8);
+    spawn(&mut app, BuildingKind::Workshop, 4);
+    app.world.send_event(BuildingCommand::Destroy { index: 8 });
+    app.world.send_event(BuildingCommand::Destroy { index: 4 });
+
+    app.update();
+
+    let hive_buildings = app.world.resource::<HiveBuildings>();
+    assert_eq!(hive_buildings.buildings[8], BuildingKind::Nexus);
+    assert_eq!(hive_buildings.buildings[4], BuildingKind::None);
+    assert_eq!(rejections(&app), [RejectReason::CannotDestroyNexus]);
+}
+
+#[test]
+fn nexus_order_spawns_baby() {
+    let mut app = buildings_app([10, 0, 0]);
+    let nexus = spawn(&mut app, BuildingKind::Nexus, 8);
+    app.world.send_event(BuildingCommand::Order { index: 8 });
+
+    app.update();
+    assert_eq!(app.world.resource::<CurrencyStorage>().stored[0], 8);
+    assert_eq!(count_bees(&mut app, BeeType::Baby), 0);
+
+    run_for(&mut app, 1.5);
+    assert_eq!(count_bees(&mut app, BeeType::Baby), 1);
+    assert_eq!(app.world.get::<Building>(nexus).unwrap().orders_count, 0);
+}
+
+#[test]
+fn armory_order_turns_regular_into_defender() {
+    let mut app = buildings_app([0, 10, 10]);
+    let armory = spawn(&mut app, BuildingKind::Armory, 6);
+    app.world.spawn(BeeBundle::new(
+        BeeType::Regular,
+        Vec3::ZERO,
+        &UnitRegistry::default(),
+    ));
+    app.world.send_event(BuildingCommand::Order { index: 6 });
+
+    run_for(&mut app, 6.0);
+
+    assert_eq!(count_bees(&mut app, BeeType::Regular), 0);
+    assert_eq!(count_bees(&mut app, BeeType::Defender(0)), 1);
+    assert_eq!(app.world.resource::<CurrencyStorage>().stored, [0, 6, 9]);
+}
+
+#[test]
+fn armory_order_waits_for_a_regular_bee() {
+    let mut app = buildings_app([0, 10, 10]);
+    let armory = spawn(&mut app, BuildingKind::Armory, 6);
+    app.world.send_event(BuildingCommand::Order { index: 6 });
+
+    run_for(&mut app, 6.0);
+
+    assert_eq!(app.world.get::<Building>(armory).unwrap().orders_count, 1);
+
+    app.world.spawn(BeeBundle::new(
+        BeeType::Regular,
+        Vec3::ZERO,
+        &UnitRegistry::default(),
+    ));
+    app.update();
+
+    assert_eq!(count_bees(&mut app, BeeType::Defender(0)), 1);
+    assert_eq!(app.world.get::<Building>(armory).unwrap().orders_count, 0);
+}
+
+#[test]
+fn upgrade_raises_defender_level() {
+    let mut app = buildings_app([0, 100, 100]);
+    spawn(&mut app, BuildingKind::Armory, 6);
+    app.world.spawn(BeeBundle::new(
+        BeeType::Defender(0),
+        Vec3::ZERO,
+        &UnitRegistry::default(),
+    ));
+    app.world.send_event(BuildingCommand::Upgrade { index: 6 });
+
+    app.update();
+
+    assert_eq!(app.world.resource::<HiveBuildings>().defender_lvl, 1);
+    assert_eq!(count_bees(&mut app, BeeType::Defender(1)), 1);
+    assert_eq!(app.world.resource::<CurrencyStorage>().stored, [0, 76, 96]);
+}
+
+#[test]
+fn order_without_currency_is_rejected() {
+    let mut app = buildings_app([1, 0, 0]);
+    let nexus = spawn(&mut app, BuildingKind::Nexus, 8);
+    app.world.send_event(BuildingCommand::Order { index: 8 });
+
+    app.update();
+
+    assert_eq!(app.world.get::<Building>(nexus).unwrap().orders_count, 0);
+    assert_eq!(rejections(&app), [RejectReason::NotEnoughCurrency]);
+}
+
+#[test]
+fn upgrade_past_max_level_is_rejected() {
+    let mut app = buildings_app([0, 1000, 1000]);
+    spawn(&mut app, BuildingKind::Armory, 6);
+    for _ in 0..3 {
+        app.world.send_event(BuildingCommand::Upgrade { index: 6 });
+    }
+
+    app.update();
+
+    assert_eq!(app.world.resource::<HiveBuildings>().defender_lvl, 2);
+    assert_eq!(rejections(&app), [RejectReason::MaxLevel]);
+    assert_eq!(app.world.resource::<CurrencyStorage>().stored, [0, 928, 988]);
+}
+
+#[test]
+fn levels_past_the_last_one_keep_its_names_and_costs() {
+    let hive_buildings = HiveBuildings {
+        defender_lvl: 5,
+        worker_lvl: 5,
+        ..default()
+    };
+
+    assert_eq!(
+        hive_buildings.get_order_name(BuildingKind::Armory),
+        "Cybernetic defender"
+    );
+    assert_eq!(
+        hive_buildings.get_order_name(BuildingKind::Workshop),
+        "Roboworker"
+    );
+    assert_eq!(hive_buildings.get_upgrade_name(BuildingKind::Workshop), "");
+    assert_eq!(
+        hive_buildings.get_order_cost(BuildingKind::Workshop),
+        [30, 4, 0]
+    );
+}
+
+#[test]
+fn destroyed_building_reverts_to_an_empty_lot() {
+    let mut app = buildings_app([0, 0, 0]);
+    app.init_resource::<GameInfo>();
+    app.add_systems(
+        Update,
+        (
+            fight_system,
+            building_destroyed_system,
+            living_creature_system,
+        )
+            .chain(),
+    );
+    let armory = spawn(&mut app, BuildingKind::Armory, 6);
+    app.world.entity_mut(armory).insert((
+        LivingCreature {
+            health: 3,
+            max_health: BuildingKind::Armory.get_max_health(),
+            ..default()
+        },
+        Faction::Bees,
+    ));
+    app.world.get_mut::<Building>(armory).unwrap().orders_count = 2;
+
+    let position = app.world.get::<Transform>(armory).unwrap().translation + Vec3::X * 10.0;
+    app.world.spawn((
+        EnemyType::Wasp(1),
+        LivingCreature {
+            time_since_last_attack: 100.0,
+            ..stats(EnemyType::Wasp(1)).get_creature()
+        },
+        stats(EnemyType::Wasp(1)).get_rigid_body(),
+        Transform::from_translation(position),
+        NavigationTarget::Entity(armory, 14.0),
+    ));
+
+    app.update();
+    // Wasp(1) hits for 2
+    assert_eq!(app.world.get::<LivingCreature>(armory).unwrap().health, 1);
+
+    run_for(&mut app, 3.0);
+    assert!(app.world.get_entity(armory).is_none());
+    assert_eq!(
+        app.world.resource::<HiveBuildings>().buildings[6],
+        BuildingKind::None
+    );
+}
+
+#[test]
+fn dead_buildings_are_not_despawned_as_creatures() {
+    let mut app = buildings_app([0, 0, 0]);
+    app.init_resource::<GameInfo>();
+    app.add_systems(Update, living_creature_system);
+    let workshop = spawn(&mut app, BuildingKind::Workshop, 4);
+    app.world.entity_mut(workshop).insert(LivingCreature {
+        health: 0,
+        time_since_last_damage_taken: 10.0,
+        ..default()
+    });
+
+    run_for(&mut app, 1.0);
+
+    assert!(app.world.get_entity(workshop).is_some());
+    assert_eq!(
+        app.world.resource::<HiveBuildings>().buildings[4],
+        BuildingKind::Workshop
+    );
+}
+
+#[test]
+fn losing_the_nexus_can_lose_the_game() {
+    let mut app = test_app();
+    app.init_resource::<HiveBuildings>();
+    app.init_resource::<CurrencyStorage>();
+    app.init_resource::<GameInfo>();
+    app.init_resource::<ScenarioRegistry>();
+    app.add_systems(Update, scenario_conditions_system);
+
+    // Only the second scenario loses with its Nexus
+    let scenario = app.world.spawn(Scenario::default()).id();
+    app.world.resource_mut::<HiveBuildings>().buildings[8] = BuildingKind::None;
+    app.update();
+    assert!(!app.world.resource::<GameInfo>().end);
+
+    app.world.get_mut::<Scenario>(scenario).unwrap().index = 1;
+    app.update();
+    let game = app.world.resource::<GameInfo>();
+    assert!(game.end && !game.victory && game.nexus_destroyed);
+}
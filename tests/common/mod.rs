@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_jam_4::core::{UnitKey, UnitRegistry, UnitStats};
+
+pub const STEP: f32 = 0.1;
+
+/// Headless app where every update advances time by [`STEP`] seconds.
+pub fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        STEP,
+    )));
+    // The very first update only starts the clock
+    app.update();
+    app
+}
+
+pub fn run_for(app: &mut App, seconds: f32) {
+    let steps = (seconds / STEP).round() as usize;
+    for _ in 0..steps {
+        app.update();
+    }
+}
+
+/// Stats of a unit in the built-in definitions.
+pub fn stats(key: impl Into<UnitKey>) -> UnitStats {
+    UnitRegistry::default().get(key)
+}
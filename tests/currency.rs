@@ -0,0 +1,79 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_jam_4::core::{gain_system, CurrencyGainPerMinute, CurrencyStorage};
+use common::{run_for, test_app};
+
+fn currency_app(stored: [u64; 3]) -> App {
+    let mut app = test_app();
+    app.insert_resource(CurrencyStorage {
+        stored,
+        max_stored: [100, 1000, 1000],
+        estimated_inflow: [0; 3],
+    });
+    app.add_systems(Update, gain_system);
+    app
+}
+
+#[test]
+fn gain_is_spread_over_a_minute() {
+    let mut app = currency_app([0, 0, 0]);
+    app.world.spawn(CurrencyGainPerMinute {
+        gain: [60, 120, 0],
+        ..default()
+    });
+
+    run_for(&mut app, 30.0);
+
+    let stored = app.world.resource::<CurrencyStorage>().stored;
+    assert!((29..=30).contains(&stored[0]), "{:?}", stored);
+    assert!((59..=60).contains(&stored[1]), "{:?}", stored);
+    assert_eq!(stored[2], 0);
+}
+
+#[test]
+fn gain_continues_into_the_next_minute() {
+    let mut app = currency_app([0, 0, 0]);
+    app.world.spawn(CurrencyGainPerMinute {
+        gain: [60, 0, 0],
+        ..default()
+    });
+
+    run_for(&mut app, 90.0);
+
+    let stored = app.world.resource::<CurrencyStorage>().stored;
+    assert!((89..=90).contains(&stored[0]), "{:?}", stored);
+}
+
+#[test]
+fn stored_currency_is_capped() {
+    let mut app = currency_app([90, 0, 0]);
+    app.world.spawn(CurrencyGainPerMinute {
+        gain: [6000, 0, 0],
+        ..default()
+    });
+
+    run_for(&mut app, 5.0);
+
+    assert_eq!(app.world.resource::<CurrencyStorage>().stored[0], 100);
+}
+
+#[test]
+fn estimated_inflow_sums_all_gainers() {
+    let mut app = currency_app([0, 0, 0]);
+    app.world.spawn(CurrencyGainPerMinute {
+        gain: [10, 1, 0],
+        ..default()
+    });
+    app.world.spawn(CurrencyGainPerMinute {
+        gain: [5, 0, 2],
+        ..default()
+    });
+
+    app.update();
+
+    assert_eq!(
+        app.world.resource::<CurrencyStorage>().estimated_inflow,
+        [15, 1, 2]
+    );
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_jam_4::{
+    core::{Elite, EliteAffix, EnemyType, LivingCreature, UnitRegistry, ARMORED_DAMAGE_REDUCTION},
+    levels::{elite_split_system, WaveSet, WaveSetLoaderError},
+};
+use common::{stats, test_app};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn ready(creature: LivingCreature) -> LivingCreature {
+    LivingCreature {
+        time_since_last_attack: 100.0,
+        ..creature
+    }
+}
+
+fn elite(affix: EliteAffix) -> (LivingCreature, Elite) {
+    let stats = stats(EnemyType::Wasp(1));
+    let mut creature = LivingCreature {
+        currency_drop: [10, 2, 2],
+        ..stats.get_creature()
+    };
+    let elite = Elite::new([affix]);
+    elite.apply(&mut creature, &mut stats.get_rigid_body());
+    (creature, elite)
+}
+
+#[test]
+fn armor_reduces_every_hit_down_to_one() {
+    let (mut wasp, _) = elite(EliteAffix::Armored);
+    assert_eq!(wasp.armor, ARMORED_DAMAGE_REDUCTION);
+
+    let mut birb = ready(stats(EnemyType::Birb(0)).get_creature());
+    let health = wasp.health;
+    birb.attack(&mut wasp, None, Vec2::X);
+    assert_eq!(
+        wasp.health,
+        health - (birb.attack_damage - ARMORED_DAMAGE_REDUCTION) as i32
+    );
+
+    let mut weak = ready(LivingCreature {
+        attack_damage: 1,
+        ..default()
+    });
+    weak.attack(&mut wasp, None, Vec2::X);
+    assert_eq!(
+        wasp.health,
+        health - (birb.attack_damage - ARMORED_DAMAGE_REDUCTION) as i32 - 1
+    );
+}
+
+#[test]
+fn vampiric_heals_on_hit() {
+    let (wasp, _) = elite(EliteAffix::Vampiric);
+    let mut wasp = ready(LivingCreature { health: 1, ..wasp });
+
+    let mut target = stats(EnemyType::Birb(0)).get_creature();
+    wasp.attack(&mut target, None, Vec2::X);
+    assert_eq!(wasp.health, 1 + wasp.attack_damage as i32);
+}
+
+#[test]
+fn affixes_change_stats_and_drop() {
+    let base = stats(EnemyType::Wasp(1));
+    let mut creature = base.get_creature();
+    let mut rigid_body = base.get_rigid_body();
+    let champion = Elite::new([EliteAffix::Swift, EliteAffix::Shielded]);
+    creature.currency_drop = [10, 2, 2];
+    champion.apply(&mut creature, &mut rigid_body);
+
+    assert!(champion.is_champion());
+    assert!(rigid_body.max_valocity > base.get_rigid_body().max_valocity);
+    assert_eq!(creature.currency_drop, [20, 4, 4]);
+    assert!(champion.get_shield(&creature).is_some());
+    assert!(Elite::new([EliteAffix::Swift])
+        .get_shield(&creature)
+        .is_none());
+}
+
+#[test]
+fn rolled_affixes_are_distinct() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..100 {
+        let elite = Elite::roll(&mut rng);
+        assert!((1..=2).contains(&elite.affixes.len()));
+        assert!(elite.affixes.first() != elite.affixes.get(1));
+    }
+}
+
+fn split_app(enemy: EnemyType) -> App {
+    let mut app = test_app();
+    app.init_resource::<Assets<Mesh>>();
+    app.init_resource::<UnitRegistry>();
+    app.add_systems(Update, elite_split_system);
+
+    app.world.spawn((
+        enemy,
+        LivingCreature {
+            health: 0,
+            currency_drop: [10, 2, 2],
+            ..stats(enemy).get_creature()
+        },
+        Elite::new([EliteAffix::Splitting, EliteAffix::Armored]),
+        Transform::default(),
+    ));
+
+    app.update();
+    app.update();
+    app
+}
+
+#[test]
+fn splitting_spawns_two_smaller_wasps_once() {
+    let mut app = split_app(EnemyType::Wasp(2));
+
+    let mut enemies = app.world.query::<(&EnemyType, &LivingCreature)>();
+    let wasps: Vec<_> = enemies
+        .iter(&app.world)
+        .filter(|(enemy, _)| **enemy == EnemyType::Wasp(1))
+        .map(|(_, creature)| creature.currency_drop)
+        .collect();
+    assert_eq!(wasps, [[5, 1, 1], [5, 1, 1]]);
+
+    let mut elites = app.world.query::<&Elite>();
+    let elite = elites.single(&app.world);
+    assert_eq!(elite.affixes, vec![EliteAffix::Armored]);
+}
+
+#[test]
+fn splitting_needs_a_smaller_wasp() {
+    let mut app = split_app(EnemyType::Wasp(0));
+
+    let mut enemies = app.world.query::<&EnemyType>();
+    assert_eq!(enemies.iter(&app.world).count(), 1);
+
+    let mut elites = app.world.query::<&Elite>();
+    let elite = elites.single(&app.world);
+    assert_eq!(elite.affixes, vec![EliteAffix::Armored]);
+}
+
+#[test]
+fn elite_chance_must_be_a_probability() {
+    let waves = br#"{
+        "waves": [
+            { "delay": 1.0, "elite_chance": 1.5, "enemies": [] }
+        ]
+    }"#;
+
+    assert!(matches!(
+        WaveSet::from_slice(waves),
+        Err(WaveSetLoaderError::InvalidWave { index: 0, .. })
+    ));
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_jam_4::core::{fight_system, BeeType, EnemyType, LivingCreature, NavigationTarget};
+use common::{stats, test_app};
+
+fn fight_app() -> App {
+    let mut app = test_app();
+    app.add_systems(Update, fight_system);
+    app
+}
+
+/// Creature that is ready to attack right away.
+fn ready(creature: LivingCreature) -> LivingCreature {
+    LivingCreature {
+        time_since_last_attack: 100.0,
+        ..creature
+    }
+}
+
+fn spawn_bee(app: &mut App, bee: BeeType, position: Vec2) -> Entity {
+    app.world
+        .spawn((
+            bee,
+            ready(stats(bee).get_creature()),
+            stats(bee).get_rigid_body(),
+            Transform::from_translation(position.extend(0.0)),
+            NavigationTarget::None,
+        ))
+        .id()
+}
+
+fn spawn_enemy(app: &mut App, enemy: EnemyType, position: Vec2) -> Entity {
+    app.world
+        .spawn((
+            enemy,
+            ready(stats(enemy).get_creature()),
+            stats(enemy).get_rigid_body(),
+            Transform::from_translation(position.extend(0.0)),
+            NavigationTarget::None,
+        ))
+        .id()
+}
+
+fn health(app: &App, e: Entity) -> i32 {
+    app.world.get::<LivingCreature>(e).unwrap().health
+}
+
+fn set_target(app: &mut App, e: Entity, target: Entity) {
+    *app.world.get_mut::<NavigationTarget>(e).unwrap() = NavigationTarget::Entity(target, 10.0);
+}
+
+#[test]
+fn bee_attacks_targeted_enemy_in_range() {
+    let mut app = fight_app();
+    let bee = spawn_bee(&mut app, BeeType::Defender(0), Vec2::ZERO);
+    let wasp = spawn_enemy(&mut app, EnemyType::Wasp(0), Vec2::new(10.0, 0.0));
+    set_target(&mut app, bee, wasp);
+
+    app.update();
+
+    let damage = stats(BeeType::Defender(0)).attack_damage as i32;
+    assert_eq!(
+        health(&app, wasp),
+        stats(EnemyType::Wasp(0)).health - damage
+    );
+    assert_eq!(health(&app, bee), stats(BeeType::Defender(0)).health);
+}
+
+#[test]
+fn enemy_attacks_targeted_bee_in_range() {
+    let mut app = fight_app();
+    let bee = spawn_bee(&mut app, BeeType::Regular, Vec2::ZERO);
+    let wasp = spawn_enemy(&mut app, EnemyType::Wasp(1), Vec2::new(0.0, 10.0));
+    set_target(&mut app, wasp, bee);
+
+    app.update();
+
+    let damage = stats(EnemyType::Wasp(1)).attack_damage as i32;
+    assert_eq!(health(&app, bee), stats(BeeType::Regular).health - damage);
+    assert_eq!(health(&app, wasp), stats(EnemyType::Wasp(1)).health);
+}
+
+#[test]
+fn no_attack_out_of_range() {
+    let mut app = fight_app();
+    let bee = spawn_bee(&mut app, BeeType::Defender(0), Vec2::ZERO);
+    let wasp = spawn_enemy(&mut app, EnemyType::Wasp(0), Vec2::new(200.0, 0.0));
+    set_target(&mut app, bee, wasp);
+    set_target(&mut app, wasp, bee);
+
+    app.update();
+
+    assert_eq!(health(&app, wasp), stats(EnemyType::Wasp(0)).health);
+    assert_eq!(health(&app, bee), stats(BeeType::Defender(0)).health);
+}
+
+#[test]
+fn attack_waits_for_cooldown() {
+    let mut app = fight_app();
+    let bee = spawn_bee(&mut app, BeeType::Defender(0), Vec2::ZERO);
+    let birb = spawn_enemy(&mut app, EnemyType::Birb(0), Vec2::new(10.0, 0.0));
+    set_target(&mut app, bee, birb);
+
+    app.update();
+    app.update();
+
+    // fight_system doesn't advance attack timers, so the second update can't attack again
+    let damage = stats(BeeType::Defender(0)).attack_damage as i32;
+    assert_eq!(
+        health(&app, birb),
+        stats(EnemyType::Birb(0)).health - damage
+    );
+    assert!(app.world.get::<LivingCreature>(birb).unwrap().accumulated_push_back.x > 0.0);
+}
+
+#[test]
+fn bees_do_not_attack_bees() {
+    let mut app = fight_app();
+    let bee = spawn_bee(&mut app, BeeType::Defender(0), Vec2::ZERO);
+    let other = spawn_bee(&mut app, BeeType::Regular, Vec2::new(5.0, 0.0));
+    set_target(&mut app, bee, other);
+
+    app.update();
+
+    assert_eq!(health(&app, other), stats(BeeType::Regular).health);
+}
@@ -0,0 +1,18 @@
+use bevy_jam_4::{
+    core::{BeeType, SimulationTick},
+    headless::{headless_app, start_headless_run},
+};
+
+#[test]
+fn headless_run_simulates_without_a_renderer() {
+    let mut app = headless_app(7, 0).unwrap();
+    start_headless_run(&mut app).unwrap();
+
+    for _ in 0..300 {
+        app.update();
+    }
+
+    assert!(app.world.resource::<SimulationTick>().0 >= 290);
+    let mut bees = app.world.query::<&BeeType>();
+    assert!(bees.iter(&app.world).any(|bee| *bee == BeeType::Queen));
+}
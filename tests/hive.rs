@@ -0,0 +1,89 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_jam_4::{
+    core::{BuildingKind, HiveBuildings, HiveDefinition, HiveMap},
+    levels::ScenarioRegistry,
+};
+
+const WALL: [u8; 4] = [40, 20, 0, 255];
+const FLOOR: [u8; 4] = [246, 180, 0, 255];
+
+fn small_hive(slots: usize) -> HiveDefinition {
+    HiveDefinition {
+        image: String::from("images/Small.png"),
+        image_size: UVec2::new(4, 2),
+        world_size: Vec2::new(40.0, 20.0),
+        passable_colors: vec![FLOOR],
+        building_slots: (0..slots as u32)
+            .map(|x| UVec2::new(x % 4, x / 4))
+            .collect(),
+    }
+}
+
+#[test]
+fn default_asset_is_the_original_hive() {
+    let hive =
+        HiveDefinition::from_slice(include_bytes!("../assets/hives/default.hive.json")).unwrap();
+
+    assert_eq!(hive, HiveDefinition::default());
+    assert_eq!(hive.get_slots_num(), 11);
+    assert!(hive.get_slot_position(8).distance(Vec2::new(42.0, -86.0)) < 1e-3);
+}
+
+#[test]
+fn invalid_hives_are_rejected() {
+    let mut outside = small_hive(2);
+    outside.building_slots.push(UVec2::new(4, 0));
+    let no_slots = small_hive(0);
+
+    for hive in [outside, no_slots] {
+        let json = serde_json::to_vec(&hive).unwrap();
+        assert!(HiveDefinition::from_slice(&json).is_err());
+    }
+}
+
+#[test]
+fn walls_come_from_the_palette() {
+    let hive = small_hive(1);
+    let mut data = vec![];
+    for i in 0..8 {
+        data.extend_from_slice(if i == 1 { &WALL } else { &FLOOR });
+    }
+    let image = Image::new(
+        Extent3d {
+            width: 4,
+            height: 2,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+
+    let map = HiveMap::from_image(&image, &hive);
+
+    // Pixel (1, 0) is the top left quarter of the hive
+    assert_eq!(map.get_obstruction(Vec2::new(-5.0, 5.0)), 1.0);
+    assert_eq!(map.get_obstruction(Vec2::new(5.0, 5.0)), 0.0);
+    assert_eq!(map.get_obstruction(Vec2::new(-5.0, -5.0)), 0.0);
+}
+
+#[test]
+fn scenario_buildings_fit_the_hive() {
+    let registry = ScenarioRegistry::default();
+    // Nexus on 8, Workshop on 4 and Armory on 6
+    let scenario = registry.get(1);
+
+    let buildings = scenario.get_buildings(&small_hive(5));
+    let hive_buildings = HiveBuildings::from_layout(small_hive(5), buildings);
+
+    assert_eq!(hive_buildings.buildings.len(), 5);
+    assert_eq!(hive_buildings.buildings[4], BuildingKind::Workshop);
+    assert_eq!(hive_buildings.get_nexus_position(), Vec2::ZERO);
+    assert_eq!(
+        hive_buildings.get_building_position(0),
+        Vec2::new(-10.0, 0.0)
+    );
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_jam_4::core::{
+    build_hive_graph_system, HiveDefinition, HiveGraph, HiveGraphCache, HiveGraphTask, HiveMap,
+    HIVE_GRAPH_POINTS_NUMBER,
+};
+use common::test_app;
+
+fn open_map() -> HiveMap {
+    HiveMap::open(&HiveDefinition::default())
+}
+
+/// The left half of the map is a wall.
+fn half_blocked_map() -> HiveMap {
+    let mut map = open_map();
+    for y in 0..map.height {
+        for x in 0..map.width / 2 {
+            map.data[x + y * map.width] = 1;
+        }
+    }
+    map
+}
+
+#[test]
+fn graph_covers_the_map() {
+    let graph = HiveGraph::build(&open_map());
+
+    assert!(graph.ready);
+    assert_eq!(graph.points.len(), HIVE_GRAPH_POINTS_NUMBER);
+    assert_eq!(graph.distances.len(), HIVE_GRAPH_POINTS_NUMBER);
+    for i in 0..graph.points.len() {
+        assert_eq!(graph.distances[i][i], 0.0);
+        for j in graph.adjacent_points[i].iter() {
+            let direct = graph.points[i].distance(graph.points[*j]);
+            assert!(graph.distances[i][*j] <= direct + 1e-3);
+        }
+    }
+    // Everything is reachable on an open map
+    assert!(graph.distances[0].iter().all(|d| *d < 1e6));
+}
+
+#[test]
+fn points_avoid_obstacles() {
+    let map = half_blocked_map();
+    let graph = HiveGraph::build(&map);
+
+    assert!(!graph.points.is_empty());
+    assert!(graph
+        .points
+        .iter()
+        .all(|p| map.get_obstruction(*p) == 0.0));
+}
+
+#[test]
+fn different_maps_hash_differently() {
+    assert_eq!(open_map().get_hash(), open_map().get_hash());
+    assert_ne!(open_map().get_hash(), half_blocked_map().get_hash());
+}
+
+#[test]
+fn cached_graph_matches_built_one() {
+    let dir = std::env::temp_dir().join(format!("hive_graph_cache_{}", std::process::id()));
+    let map = half_blocked_map();
+
+    let built = HiveGraph::load_or_build(&map, Some(&dir));
+    let cached = HiveGraph::load_or_build(&map, Some(&dir));
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(built.points, cached.points);
+    assert_eq!(built.distances, cached.distances);
+    assert_eq!(built.next_points, cached.next_points);
+    assert_eq!(cached.map_hash, map.get_hash());
+}
+
+#[test]
+fn graph_is_built_in_the_background() {
+    let mut app = test_app();
+    app.insert_resource(open_map());
+    app.init_resource::<HiveGraph>();
+    app.init_resource::<HiveGraphTask>();
+    app.insert_resource(HiveGraphCache(None));
+    app.add_systems(Update, build_hive_graph_system);
+
+    for _ in 0..10_000 {
+        app.update();
+        if app.world.resource::<HiveGraph>().ready {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+
+    let graph = app.world.resource::<HiveGraph>();
+    assert!(graph.ready);
+    assert_eq!(graph.map_hash, open_map().get_hash());
+}
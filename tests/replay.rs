@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_jam_4::{
+    core::{BuildingCommand, BuildingKind, LivingCreature, SimulationTick, SIMULATION_HZ},
+    headless::{headless_app, start_headless_run},
+    replay::{Replay, ReplayPlayback, ReplayPlugin, ReplayRecording},
+};
+
+/// Short enough that the first wave doesn't clear and write the replay file.
+const RECORDED_TICKS: u64 = 900;
+
+/// Makes every update run `steps` fixed steps, like a slow frame rate does.
+fn set_steps_per_update(app: &mut App, steps: u32) {
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        steps as f64 / SIMULATION_HZ,
+    )));
+}
+
+/// Stands in for the materials the game inserts outside the simulation, which
+/// moves units to another archetype whenever a frame happens to run.
+#[derive(Component)]
+struct Visuals;
+
+fn insert_visuals_system(
+    mut commands: Commands,
+    creatures: Query<Entity, (With<LivingCreature>, Without<Visuals>)>,
+) {
+    for e in creatures.iter() {
+        commands.entity(e).insert(Visuals);
+    }
+}
+
+fn get_tick(app: &App) -> u64 {
+    app.world.resource::<SimulationTick>().0
+}
+
+fn record(steps_per_update: u32) -> Replay {
+    let mut app = headless_app(11, 3).unwrap();
+    app.add_plugins(ReplayPlugin);
+    app.add_systems(PreUpdate, insert_visuals_system);
+    start_headless_run(&mut app).unwrap();
+    set_steps_per_update(&mut app, steps_per_update);
+
+    let mut sent = [false; 3];
+    while get_tick(&app) < RECORDED_TICKS {
+        if get_tick(&app) >= 120 && !sent[0] {
+            app.world.send_event(BuildingCommand::Order { index: 8 });
+            app.world.send_event(BuildingCommand::Build {
+                index: 4,
+                kind: BuildingKind::Workshop,
+            });
+            sent[0] = true;
+        }
+        // Right after the build, whether the building is there yet must not
+        // depend on how many steps a frame ran
+        if get_tick(&app) >= 122 && !sent[1] {
+            app.world.send_event(BuildingCommand::Order { index: 4 });
+            sent[1] = true;
+        }
+        if get_tick(&app) >= 300 && !sent[2] {
+            app.world.send_event(BuildingCommand::Order { index: 8 });
+            sent[2] = true;
+        }
+        app.update();
+    }
+    app.world
+        .resource::<ReplayRecording>()
+        .0
+        .clone()
+        .expect("the run is recorded")
+}
+
+/// Returns the tick the replay diverged at, if it did.
+fn play_back(replay: Replay, steps_per_update: u32) -> Option<u64> {
+    let mut app = headless_app(replay.seed, replay.scenario).unwrap();
+    app.add_plugins(ReplayPlugin);
+    app.add_systems(PreUpdate, insert_visuals_system);
+    // Entities get different indices than in the recorded run
+    for _ in 0..50 {
+        app.world.spawn_empty();
+    }
+    app.insert_resource(ReplayPlayback::new(replay));
+    start_headless_run(&mut app).unwrap();
+    set_steps_per_update(&mut app, steps_per_update);
+
+    for _ in 0..RECORDED_TICKS * 2 {
+        if app.world.resource::<ReplayPlayback>().finished {
+            break;
+        }
+        app.update();
+    }
+
+    let playback = app.world.resource::<ReplayPlayback>();
+    assert!(playback.finished);
+    playback.diverged_at
+}
+
+#[test]
+fn replay_reproduces_the_recorded_checksums() {
+    let replay = record(1);
+    assert!(!replay.entries.is_empty());
+    assert!(replay.checksums.len() > 5);
+
+    assert_eq!(play_back(replay, 1), None);
+}
+
+#[test]
+fn replay_does_not_depend_on_the_frame_rate() {
+    let replay = record(1);
+    assert_eq!(play_back(replay, 5), None);
+
+    let replay = record(5);
+    assert!(!replay.entries.is_empty());
+    assert_eq!(play_back(replay, 1), None);
+}
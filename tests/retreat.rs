@@ -0,0 +1,96 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_jam_4::core::{
+    get_retreat_position, retreat_heal_system, BeeType, EnemyType, Faction, HiveDefinition,
+    HiveMap, LivingCreature, RETREAT_HEAL_INTERVAL,
+};
+use common::{run_for, stats, test_app};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn open_map() -> HiveMap {
+    HiveMap::open(&HiveDefinition::default())
+}
+
+fn wounded(health: i32) -> LivingCreature {
+    LivingCreature {
+        health,
+        time_since_last_damage_taken: 100.0,
+        ..stats(BeeType::Defender(0)).get_creature()
+    }
+}
+
+#[test]
+fn retreating_creature_heals() {
+    let mut app = test_app();
+    app.add_systems(Update, retreat_heal_system);
+
+    let mut behaviour = stats(BeeType::Defender(0)).get_behaviour();
+    behaviour.retreating = true;
+    let e = app.world.spawn((behaviour, wounded(1))).id();
+
+    run_for(&mut app, RETREAT_HEAL_INTERVAL * 2.0 + 0.5);
+
+    assert_eq!(app.world.get::<LivingCreature>(e).unwrap().health, 3);
+}
+
+#[test]
+fn only_retreating_creatures_heal() {
+    let mut app = test_app();
+    app.add_systems(Update, retreat_heal_system);
+
+    let e = app
+        .world
+        .spawn((stats(BeeType::Defender(0)).get_behaviour(), wounded(1)))
+        .id();
+
+    run_for(&mut app, RETREAT_HEAL_INTERVAL * 4.0);
+
+    assert_eq!(app.world.get::<LivingCreature>(e).unwrap().health, 1);
+}
+
+#[test]
+fn bees_retreat_towards_the_birther() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let nexus = Vec2::new(40.0, -80.0);
+    let threat = Vec2::new(-60.0, 20.0);
+
+    let pos = get_retreat_position(
+        Vec2::new(-40.0, 0.0),
+        threat,
+        nexus,
+        &Faction::Bees,
+        &open_map(),
+        &mut rng,
+    );
+
+    assert!(pos.distance(nexus) <= 40.0 * 2f32.sqrt());
+    assert!(pos.distance(threat) >= nexus.distance(threat));
+}
+
+#[test]
+fn enemies_retreat_away_from_the_hive() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let position = Vec2::new(100.0, 0.0);
+
+    let pos = get_retreat_position(
+        position,
+        Vec2::new(80.0, 0.0),
+        Vec2::ZERO,
+        &Faction::Enemies,
+        &open_map(),
+        &mut rng,
+    );
+
+    assert!(pos.length() > position.length());
+}
+
+#[test]
+fn badly_wounded_enemies_can_retreat() {
+    let behaviour = stats(EnemyType::Birb(0)).get_behaviour();
+    assert!(behaviour.run_away_radius > 0.0);
+    assert_eq!(
+        behaviour.min_healthpoints_before_run_away,
+        stats(EnemyType::Birb(0)).health / 4
+    );
+}
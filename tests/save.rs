@@ -0,0 +1,208 @@
+use std::path::PathBuf;
+
+use bevy::{ecs::system::SystemState, prelude::*};
+use bevy_jam_4::{
+    core::{
+        Building, BuildingKind, GameRng, HiveAlarm, HiveBuildings, NavigationTarget, Shield,
+        ShieldCooldown, SimulationTick, SpawnOrder, UniversalBehaviour,
+    },
+    headless::{headless_app, start_headless_run},
+    replay::ChecksumSource,
+    save::{PendingLoad, SaveGameSource, SavePlugin, SaveRequest, SavedNavigationTarget},
+};
+
+const TICKS_AFTER_LOAD: usize = 600;
+
+fn save_app(seed: u64) -> App {
+    let mut app = headless_app(seed, 0).unwrap();
+    app.add_plugins(SavePlugin);
+    app
+}
+
+fn load(app: &mut App, path: &PathBuf) {
+    app.world.resource_mut::<PendingLoad>().0 = Some(path.clone());
+    while app.world.resource::<PendingLoad>().0.is_some() {
+        app.update();
+    }
+}
+
+fn behaviours(app: &mut App) -> Vec<(u32, bool, u32)> {
+    let mut behaviours: Vec<_> = app
+        .world
+        .query::<&UniversalBehaviour>()
+        .iter(&app.world)
+        .map(|b| {
+            (
+                b.time_since_last_refresh.to_bits(),
+                b.retreating,
+                b.heal_progress.to_bits(),
+            )
+        })
+        .collect();
+    behaviours.sort_unstable();
+    behaviours
+}
+
+fn timers(app: &App) -> (u64, u32, Vec<(u32, u32, u32)>) {
+    let alarms = app
+        .world
+        .resource::<HiveAlarm>()
+        .alarms
+        .iter()
+        .map(|alarm| {
+            (
+                alarm.position.x.to_bits(),
+                alarm.position.y.to_bits(),
+                alarm.age.to_bits(),
+            )
+        })
+        .collect();
+    (
+        app.world.resource::<SimulationTick>().0,
+        app.world.resource::<ShieldCooldown>().0.to_bits(),
+        alarms,
+    )
+}
+
+fn checksum_after(app: &mut App, ticks: usize) -> u64 {
+    for _ in 0..ticks {
+        app.update();
+    }
+    let mut source = SystemState::<ChecksumSource>::new(&mut app.world);
+    source.get(&app.world).checksum()
+}
+
+#[test]
+fn loaded_game_continues_like_the_saved_one() {
+    let path = std::env::temp_dir().join("bevy_jam_4_save_round_trip.bin");
+
+    let mut app = save_app(5);
+    start_headless_run(&mut app).unwrap();
+    for _ in 0..300 {
+        app.update();
+    }
+    app.world.send_event(SaveRequest(path.clone()));
+    app.update();
+    let word_positions = app.world.resource::<GameRng>().word_positions();
+    let saved_behaviours = behaviours(&mut app);
+
+    // Navigation paths are recomputed after a load, so the reference is the
+    // same save loaded over the running game
+    load(&mut app, &path);
+    assert_eq!(
+        app.world.resource::<GameRng>().word_positions(),
+        word_positions
+    );
+    assert_eq!(behaviours(&mut app), saved_behaviours);
+    let expected = checksum_after(&mut app, TICKS_AFTER_LOAD);
+
+    // Another seed and other entity indices, all of it must come from the save
+    let mut app = save_app(6);
+    for _ in 0..50 {
+        app.world.spawn_empty();
+    }
+    start_headless_run(&mut app).unwrap();
+    load(&mut app, &path);
+    assert_eq!(
+        app.world.resource::<GameRng>().word_positions(),
+        word_positions
+    );
+    assert_eq!(app.world.resource::<GameRng>().seed(), 5);
+    assert_eq!(checksum_after(&mut app, TICKS_AFTER_LOAD), expected);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn targeted_buildings_are_restored() {
+    let path = std::env::temp_dir().join("bevy_jam_4_save_building_target.bin");
+
+    let mut app = save_app(5);
+    start_headless_run(&mut app).unwrap();
+    for _ in 0..10 {
+        app.update();
+    }
+    let (nexus, index) = app
+        .world
+        .query::<(Entity, &Building)>()
+        .iter(&app.world)
+        .find(|(_, building)| building.kind == BuildingKind::Nexus)
+        .map(|(e, building)| (e, building.index))
+        .unwrap();
+    let (_, order, mut target) = app
+        .world
+        .query::<(&UniversalBehaviour, &SpawnOrder, &mut NavigationTarget)>()
+        .iter_mut(&mut app.world)
+        .next()
+        .unwrap();
+    let order = *order;
+    *target = NavigationTarget::Entity(nexus, 7.0);
+
+    // Captured directly, a step in between would refresh the target
+    let mut source = SystemState::<SaveGameSource>::new(&mut app.world);
+    let save = source.get(&app.world).capture().unwrap();
+    assert!(save
+        .units
+        .iter()
+        .any(|unit| unit.target == SavedNavigationTarget::Building(index, 7.0)));
+    save.write(&path).unwrap();
+
+    let mut app = save_app(6);
+    for _ in 0..50 {
+        app.world.spawn_empty();
+    }
+    start_headless_run(&mut app).unwrap();
+    load(&mut app, &path);
+
+    let target = app
+        .world
+        .query::<(&SpawnOrder, &NavigationTarget)>()
+        .iter(&app.world)
+        .find(|(unit_order, _)| **unit_order == order)
+        .map(|(_, target)| *target)
+        .unwrap();
+    let NavigationTarget::Entity(e, range) = target else {
+        panic!("the target was lost on load");
+    };
+    assert_eq!(range, 7.0);
+    assert_eq!(app.world.get::<Building>(e).map(|b| b.index), Some(index));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn shields_and_alarms_continue_after_a_load() {
+    let path = std::env::temp_dir().join("bevy_jam_4_save_shields_and_alarms.bin");
+
+    let mut app = save_app(5);
+    start_headless_run(&mut app).unwrap();
+    app.update();
+    app.world.resource_mut::<HiveBuildings>().shield_lvl = 1;
+    for _ in 0..200 {
+        app.update();
+    }
+    app.world
+        .resource_mut::<HiveAlarm>()
+        .raise(Vec2::new(30.0, -20.0));
+    app.world.send_event(SaveRequest(path.clone()));
+    app.update();
+    let saved_timers = timers(&app);
+    assert!(!saved_timers.2.is_empty());
+    let mut shields = app.world.query::<&Shield>();
+    assert!(shields.iter(&app.world).next().is_some());
+
+    load(&mut app, &path);
+    assert_eq!(timers(&app), saved_timers);
+    let expected = checksum_after(&mut app, TICKS_AFTER_LOAD);
+
+    let mut app = save_app(6);
+    start_headless_run(&mut app).unwrap();
+    for _ in 0..20 {
+        app.update();
+    }
+    load(&mut app, &path);
+    assert_eq!(timers(&app), saved_timers);
+    assert_eq!(checksum_after(&mut app, TICKS_AFTER_LOAD), expected);
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_jam_4::core::{
+    integration_system, separation_system, spatial_grid_system, BeeType, EnemyType, Faction,
+    GameRng, HiveDefinition, HiveMap, SpatialGrid,
+};
+use common::{run_for, stats, test_app};
+
+fn physics_app() -> App {
+    let mut app = test_app();
+    app.init_resource::<SpatialGrid>();
+    app.init_resource::<GameRng>();
+    app.insert_resource(HiveMap::open(&HiveDefinition::default()));
+    app.add_systems(
+        Update,
+        (spatial_grid_system, separation_system, integration_system).chain(),
+    );
+    app
+}
+
+fn spawn_bee(app: &mut App, bee: BeeType, position: Vec2) -> Entity {
+    app.world
+        .spawn((
+            stats(bee).get_creature(),
+            stats(bee).get_rigid_body(),
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            Faction::Bees,
+        ))
+        .id()
+}
+
+fn spawn_wasp(app: &mut App, position: Vec2) -> Entity {
+    let enemy = EnemyType::Wasp(0);
+    app.world
+        .spawn((
+            stats(enemy).get_creature(),
+            stats(enemy).get_rigid_body(),
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            Faction::Enemies,
+        ))
+        .id()
+}
+
+fn distance(app: &App, a: Entity, b: Entity) -> f32 {
+    let a = app.world.get::<Transform>(a).unwrap().translation;
+    let b = app.world.get::<Transform>(b).unwrap().translation;
+    a.truncate().distance(b.truncate())
+}
+
+#[test]
+fn stacked_bees_spread_out() {
+    let mut app = physics_app();
+    let a = spawn_bee(&mut app, BeeType::Regular, Vec2::new(50.0, 50.0));
+    let b = spawn_bee(&mut app, BeeType::Regular, Vec2::new(50.0, 50.0));
+
+    run_for(&mut app, 2.0);
+
+    let rb = stats(BeeType::Regular).get_rigid_body();
+    assert!(distance(&app, a, b) > rb.radius * 2.0);
+}
+
+#[test]
+fn stacked_wasps_spread_out() {
+    let mut app = physics_app();
+    let a = spawn_wasp(&mut app, Vec2::new(50.0, 50.0));
+    let b = spawn_wasp(&mut app, Vec2::new(52.0, 50.0));
+
+    run_for(&mut app, 2.0);
+
+    assert!(distance(&app, a, b) > 10.0);
+}
+
+#[test]
+fn spaced_out_bees_stay_put() {
+    let mut app = physics_app();
+    let a = spawn_bee(&mut app, BeeType::Regular, Vec2::new(50.0, 50.0));
+    let b = spawn_bee(&mut app, BeeType::Regular, Vec2::new(80.0, 50.0));
+
+    run_for(&mut app, 1.0);
+
+    assert_eq!(distance(&app, a, b), 30.0);
+}
+
+#[test]
+fn separation_ignores_the_other_faction() {
+    let mut app = physics_app();
+    let bee = spawn_bee(&mut app, BeeType::Regular, Vec2::new(50.0, 50.0));
+    let wasp = spawn_wasp(&mut app, Vec2::new(55.0, 50.0));
+
+    run_for(&mut app, 1.0);
+
+    assert_eq!(distance(&app, bee, wasp), 5.0);
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_jam_4::core::{
+    building_commands_system, fight_system, queue_building_commands_system, shield_grant_system,
+    shield_system, BeeBundle, BeeType, BuildRequested, BuildingCommand, BuildingCommandQueue,
+    BuildingCommandRejected, BuildingCommandSucceeded, CurrencyStorage, DestroyRequested,
+    EnemyType, GameRng, HiveBuildings, LivingCreature, NavigationTarget, OrderRequested, Shield,
+    ShieldCooldown, SpawnOrder, UnitRegistry, UpgradeRequested, SHIELD_REGEN_DELAY,
+};
+use common::{run_for, stats, test_app};
+
+#[test]
+fn shield_absorbs_damage_before_health() {
+    let mut app = test_app();
+    app.add_systems(Update, fight_system);
+
+    let bee = app
+        .world
+        .spawn((
+            BeeType::Regular,
+            stats(BeeType::Regular).get_creature(),
+            stats(BeeType::Regular).get_rigid_body(),
+            Transform::default(),
+            NavigationTarget::None,
+            Shield::new(4),
+        ))
+        .id();
+    app.world.spawn((
+        EnemyType::Wasp(1),
+        LivingCreature {
+            time_since_last_attack: 100.0,
+            ..stats(EnemyType::Wasp(1)).get_creature()
+        },
+        stats(EnemyType::Wasp(1)).get_rigid_body(),
+        Transform::from_xyz(10.0, 0.0, 0.0),
+        NavigationTarget::Entity(bee, 10.0),
+    ));
+
+    app.update();
+
+    // Wasp(1) hits for 2, all of it taken by the shield
+    assert_eq!(app.world.get::<Shield>(bee).unwrap().amount, 2);
+    let creature = app.world.get::<LivingCreature>(bee).unwrap();
+    assert_eq!(creature.health, stats(BeeType::Regular).health);
+}
+
+#[test]
+fn shield_overflow_damages_health() {
+    let mut shield = Shield::new(2);
+    assert_eq!(shield.absorb(5), 3);
+    assert_eq!(shield.amount, 0);
+    assert_eq!(shield.absorb(1), 1);
+}
+
+#[test]
+fn shield_regenerates_after_delay() {
+    let mut app = test_app();
+    app.add_systems(Update, shield_system);
+
+    let mut shield = Shield::new(4);
+    shield.absorb(3);
+    let e = app.world.spawn(shield).id();
+
+    run_for(&mut app, SHIELD_REGEN_DELAY - 1.0);
+    assert_eq!(app.world.get::<Shield>(e).unwrap().amount, 1);
+
+    run_for(&mut app, 3.5);
+    assert_eq!(app.world.get::<Shield>(e).unwrap().amount, 3);
+
+    run_for(&mut app, 10.0);
+    assert_eq!(app.world.get::<Shield>(e).unwrap().amount, 4);
+}
+
+#[test]
+fn nexus_upgrade_gives_shields_to_bees() {
+    let mut app = test_app();
+    app.init_resource::<HiveBuildings>();
+    app.init_resource::<GameRng>();
+    app.init_resource::<ShieldCooldown>();
+    app.init_resource::<BuildingCommandQueue>();
+    app.insert_resource(CurrencyStorage {
+        stored: [0, 0, 100],
+        ..default()
+    });
+    app.add_event::<BuildingCommand>();
+    app.add_event::<BuildRequested>();
+    app.add_event::<UpgradeRequested>();
+    app.add_event::<DestroyRequested>();
+    app.add_event::<OrderRequested>();
+    app.add_event::<BuildingCommandSucceeded>();
+    app.add_event::<BuildingCommandRejected>();
+    app.add_systems(PreUpdate, queue_building_commands_system);
+    app.add_systems(Update, (building_commands_system, shield_grant_system).chain());
+
+    for _ in 0..3 {
+        app.world.spawn(BeeBundle::new(
+            BeeType::Regular,
+            Vec3::ZERO,
+            &UnitRegistry::default(),
+        ));
+    }
+
+    let mut shields = app.world.query::<&Shield>();
+
+    run_for(&mut app, 5.0);
+    assert_eq!(shields.iter(&app.world).count(), 0);
+
+    // The birther sits in slot 8 by default
+    app.world.send_event(BuildingCommand::Upgrade { index: 8 });
+    app.update();
+    assert_eq!(app.world.resource::<HiveBuildings>().shield_lvl, 1);
+    assert_eq!(shields.iter(&app.world).count(), 1);
+
+    let interval = app.world.resource::<HiveBuildings>().get_shield_interval();
+    run_for(&mut app, interval + 0.5);
+    assert_eq!(shields.iter(&app.world).count(), 2);
+
+    // Shields already handed out grow with the next level
+    app.world.send_event(BuildingCommand::Upgrade { index: 8 });
+    app.update();
+    let capacity = app.world.resource::<HiveBuildings>().get_shield_capacity();
+    assert!(capacity > 4);
+    assert!(shields
+        .iter(&app.world)
+        .all(|shield| shield.max_amount == capacity && shield.amount == capacity));
+}
+
+/// Spawn order of the bee the first shield goes to.
+fn get_first_shielded(spawn_orders: &[u64]) -> u64 {
+    let mut app = test_app();
+    let mut hive_buildings = HiveBuildings::default();
+    hive_buildings.shield_lvl = 1;
+    app.insert_resource(hive_buildings);
+    app.init_resource::<GameRng>();
+    app.init_resource::<ShieldCooldown>();
+    app.add_systems(Update, shield_grant_system);
+
+    for order in spawn_orders {
+        app.world.spawn((
+            BeeBundle::new(BeeType::Regular, Vec3::ZERO, &UnitRegistry::default()),
+            SpawnOrder(*order),
+        ));
+    }
+    app.update();
+
+    let mut shielded = app.world.query_filtered::<&SpawnOrder, With<Shield>>();
+    let shielded: Vec<u64> = shielded.iter(&app.world).map(|order| order.0).collect();
+    assert_eq!(shielded.len(), 1);
+    shielded[0]
+}
+
+#[test]
+fn shield_goes_to_the_same_bee_whatever_the_storage_order() {
+    let orders: Vec<u64> = (0..8).collect();
+    let reversed: Vec<u64> = orders.iter().rev().copied().collect();
+    assert_eq!(get_first_shielded(&orders), get_first_shielded(&reversed));
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use bevy_jam_4::core::{Faction, SpatialEntry, SpatialGrid};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn random_grid(count: usize, rng: &mut StdRng) -> (SpatialGrid, Vec<SpatialEntry>) {
+    let mut grid = SpatialGrid::default();
+    let mut entries = vec![];
+    for i in 0..count {
+        let entry = SpatialEntry {
+            entity: Entity::from_raw(i as u32),
+            position: Vec2::new(rng.gen_range(-800.0..800.0), rng.gen_range(-800.0..800.0)),
+            radius: rng.gen_range(4.0..13.0),
+            is_bee: rng.gen_bool(0.5),
+            building: None,
+        };
+        grid.insert(entry);
+        entries.push(entry);
+    }
+    (grid, entries)
+}
+
+#[test]
+fn nearest_matches_brute_force() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let (grid, entries) = random_grid(500, &mut rng);
+
+    for _ in 0..200 {
+        let center = Vec2::new(rng.gen_range(-1000.0..1000.0), rng.gen_range(-1000.0..1000.0));
+        let max_distance = rng.gen_range(10.0..2000.0);
+
+        let expected = entries
+            .iter()
+            .filter(|e| e.is_enemy_of(&Faction::Bees))
+            .map(|e| e.position.distance_squared(center))
+            .filter(|d| *d < max_distance * max_distance)
+            .min_by(f32::total_cmp);
+        let found = grid
+            .find_nearest(center, max_distance, |e| e.is_enemy_of(&Faction::Bees))
+            .map(|(_, d)| d);
+
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn radius_query_matches_brute_force() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let (grid, entries) = random_grid(500, &mut rng);
+
+    for _ in 0..100 {
+        let center = Vec2::new(rng.gen_range(-800.0..800.0), rng.gen_range(-800.0..800.0));
+        let radius = rng.gen_range(0.0..300.0);
+
+        let mut found = vec![];
+        grid.for_each_in_radius(center, radius, |e| found.push(e.entity));
+        found.sort();
+        let mut expected: Vec<Entity> = entries
+            .iter()
+            .filter(|e| e.position.distance_squared(center) <= radius * radius)
+            .map(|e| e.entity)
+            .collect();
+        expected.sort();
+
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn empty_grid_finds_nothing() {
+    let grid = SpatialGrid::default();
+
+    assert!(grid.find_nearest(Vec2::ZERO, 1000.0, |_| true).is_none());
+}
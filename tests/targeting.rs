@@ -0,0 +1,114 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_jam_4::core::{
+    BeeType, BuildingKind, EnemyType, HiveDefinition, TargetCandidate, TargetingStrategy,
+};
+use common::stats;
+
+fn candidate(index: u32, position: Vec2, health: i32, bee: BeeType) -> TargetCandidate {
+    TargetCandidate {
+        entity: Entity::from_raw(index),
+        position,
+        dist_sqr: position.distance_squared(Vec2::new(400.0, 0.0)),
+        health,
+        bee: Some(bee),
+        building: None,
+    }
+}
+
+/// Seen from an enemy at (400, 0), right of the hive.
+fn candidates() -> Vec<TargetCandidate> {
+    vec![
+        candidate(0, Vec2::new(0.0, 0.0), 20, BeeType::Queen),
+        candidate(1, Vec2::new(350.0, 0.0), 10, BeeType::Defender(1)),
+        candidate(2, Vec2::new(100.0, 0.0), 4, BeeType::Worker(1)),
+        candidate(3, Vec2::new(-300.0, 0.0), 4, BeeType::Worker(1)),
+    ]
+}
+
+fn select(strategy: TargetingStrategy, candidates: &[TargetCandidate]) -> Option<u32> {
+    strategy
+        .select(candidates, &HiveDefinition::default())
+        .map(|candidate| candidate.entity.index())
+}
+
+#[test]
+fn strategies_pick_their_preferred_target() {
+    let candidates = candidates();
+
+    assert_eq!(select(TargetingStrategy::Nearest, &candidates), Some(1));
+    // Both workers are as weak, the closer one wins
+    assert_eq!(select(TargetingStrategy::Weakest, &candidates), Some(2));
+    // Only the second worker is outside the hive
+    assert_eq!(select(TargetingStrategy::Workers, &candidates), Some(3));
+    assert_eq!(select(TargetingStrategy::Queen, &candidates), Some(0));
+
+    let mut with_building = candidates.clone();
+    with_building.push(TargetCandidate {
+        bee: None,
+        building: Some(BuildingKind::Storage),
+        ..candidate(4, Vec2::new(-100.0, 50.0), 120, BeeType::Regular)
+    });
+    assert_eq!(
+        select(TargetingStrategy::Buildings, &with_building),
+        Some(4)
+    );
+    assert_eq!(select(TargetingStrategy::Buildings, &candidates), Some(1));
+}
+
+#[test]
+fn strategies_fall_back_to_the_nearest_target() {
+    let candidates: Vec<_> = candidates()
+        .into_iter()
+        .filter(|candidate| candidate.entity.index() < 3 && candidate.bee != Some(BeeType::Queen))
+        .collect();
+
+    assert_eq!(select(TargetingStrategy::Workers, &candidates), Some(1));
+    assert_eq!(select(TargetingStrategy::Queen, &candidates), Some(1));
+    assert_eq!(select(TargetingStrategy::Nearest, &[]), None);
+}
+
+#[test]
+fn strategies_attack_buildings_when_no_units_are_left() {
+    let building = TargetCandidate {
+        bee: None,
+        building: Some(BuildingKind::Storage),
+        ..candidate(4, Vec2::new(380.0, 0.0), 1, BeeType::Regular)
+    };
+    let mut candidates = candidates();
+    candidates.push(building);
+
+    // Closer and weaker than any bee, still only a fallback
+    assert_eq!(select(TargetingStrategy::Nearest, &candidates), Some(1));
+    assert_eq!(select(TargetingStrategy::Weakest, &candidates), Some(2));
+
+    for strategy in [
+        TargetingStrategy::Nearest,
+        TargetingStrategy::Weakest,
+        TargetingStrategy::Workers,
+        TargetingStrategy::Queen,
+    ] {
+        assert_eq!(select(strategy, &[building]), Some(4));
+    }
+}
+
+#[test]
+fn default_units_have_personalities() {
+    assert_eq!(
+        stats(EnemyType::Wasp(0)).targeting,
+        TargetingStrategy::Weakest
+    );
+    assert_eq!(
+        stats(EnemyType::Birb(1)).targeting,
+        TargetingStrategy::Workers
+    );
+    assert_eq!(
+        stats(EnemyType::Bumble(2)).targeting,
+        TargetingStrategy::Queen
+    );
+    assert_eq!(
+        stats(BeeType::Defender(1)).targeting,
+        TargetingStrategy::Nearest
+    );
+}
@@ -0,0 +1,114 @@
+use bevy_jam_4::core::{
+    BeeType, EnemyType, UnitDefinitions, UnitDefinitionsLoaderError, UnitId, UnitRegistry,
+};
+use serde_json::Value;
+
+fn default_units() -> Value {
+    serde_json::from_slice(include_bytes!("../assets/units/default.units.json")).unwrap()
+}
+
+fn load(units: &Value) -> Result<UnitDefinitions, UnitDefinitionsLoaderError> {
+    UnitDefinitions::from_slice(&serde_json::to_vec(units).unwrap())
+}
+
+#[test]
+fn default_units_keep_the_original_stats() {
+    let units = UnitRegistry::default();
+
+    let defender = units.get(BeeType::Defender(1));
+    assert_eq!(defender.health, 10);
+    assert_eq!(defender.attack_damage, 3);
+    assert_eq!(defender.get_rigid_body().radius, 6.0);
+
+    let worker = units.get(BeeType::Worker(2));
+    assert_eq!(worker.health, 8);
+    assert_eq!(worker.get_gain().gain, [20, 10, 0]);
+
+    let birb = units.get(EnemyType::Birb(2));
+    assert_eq!(birb.health, 240);
+    assert_eq!(birb.attack_radius, 28.0);
+    assert_eq!(birb.get_behaviour().min_healthpoints_before_run_away, 60);
+
+    assert!(units.get(BeeType::Queen).end_game_on_dead);
+}
+
+#[test]
+fn levels_past_the_last_one_grow_from_it() {
+    let units = UnitRegistry::default();
+    let wasp = units.get(EnemyType::Wasp(1));
+
+    let stronger = units.get(EnemyType::Wasp(3));
+    assert_eq!(stronger.health, 36);
+    assert_eq!(stronger.attack_damage, 5);
+    assert!(stronger.attack_cooldown < wasp.attack_cooldown);
+    assert!(stronger.max_valocity > wasp.max_valocity);
+    assert_eq!(stronger.overlay, wasp.overlay);
+    assert_eq!(stronger.get_drop([10, 2, 2]), [23, 5, 5]);
+}
+
+#[test]
+fn any_level_can_be_spawned() {
+    let units = UnitRegistry::default();
+
+    for level in [3, 10, 100] {
+        let birb = units.get(EnemyType::Birb(level));
+        assert!(birb.health >= units.get(EnemyType::Birb(2)).health);
+        birb.get_creature();
+        birb.get_material();
+    }
+}
+
+#[test]
+fn missing_level_is_an_error() {
+    let mut units = default_units();
+    units["defender"]["levels"].as_array_mut().unwrap().pop();
+
+    assert!(matches!(
+        load(&units),
+        Err(UnitDefinitionsLoaderError::MissingLevels {
+            unit: UnitId::Defender,
+            found: 2,
+            required: 3,
+        })
+    ));
+}
+
+#[test]
+fn missing_unit_is_an_error() {
+    let mut units = default_units();
+    units.as_object_mut().unwrap().remove("bumble");
+
+    assert!(matches!(
+        load(&units),
+        Err(UnitDefinitionsLoaderError::MissingUnit(UnitId::Bumble))
+    ));
+}
+
+#[test]
+fn shrinking_growth_is_an_error() {
+    let mut units = default_units();
+    units["birb"]["growth"]["speed"] = Value::from(0.0);
+
+    assert!(matches!(
+        load(&units),
+        Err(UnitDefinitionsLoaderError::InvalidGrowth {
+            unit: UnitId::Birb,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn malformed_level_is_an_error() {
+    let mut units = default_units();
+    units["wasp"]["levels"][1]["health"] = Value::from("lots");
+
+    assert!(matches!(
+        load(&units),
+        Err(UnitDefinitionsLoaderError::InvalidLevel {
+            unit: UnitId::Wasp,
+            level: 1,
+            ..
+        })
+    ));
+}
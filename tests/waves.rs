@@ -0,0 +1,104 @@
+use bevy_jam_4::{
+    core::EnemyType,
+    levels::{
+        EndlessWaveGenerator, WaveSet, WaveSetLoaderError, MAX_ENDLESS_ENEMIES,
+        MAX_ENDLESS_EXTRA_LEVELS,
+    },
+};
+
+fn generator() -> EndlessWaveGenerator {
+    EndlessWaveGenerator {
+        seed: 3,
+        delay: 40.0,
+        base_budget: 180.0,
+        budget_growth: 1.12,
+        max_single_enemy_share: 0.2,
+        elite_chance: 0.1,
+    }
+}
+
+fn enemies(generator: &EndlessWaveGenerator, round: usize, run_seed: u64) -> Vec<EnemyType> {
+    generator
+        .generate(round, run_seed)
+        .enemies
+        .iter()
+        .map(|spawn| spawn.enemy)
+        .collect()
+}
+
+#[test]
+fn same_seeds_give_the_same_waves() {
+    let generator = generator();
+
+    for round in [0, 5, 30] {
+        assert_eq!(
+            enemies(&generator, round, 42),
+            enemies(&generator, round, 42)
+        );
+    }
+    assert_ne!(enemies(&generator, 0, 42), enemies(&generator, 0, 43));
+}
+
+#[test]
+fn late_rounds_level_enemies_up_instead_of_adding_more() {
+    let generator = generator();
+    let early = enemies(&generator, 0, 42);
+    assert!(early.iter().all(|enemy| enemy.get_level() <= 2));
+
+    let late = enemies(&generator, 30, 42);
+    assert_eq!(late.len(), MAX_ENDLESS_ENEMIES);
+    assert!(late.iter().all(|enemy| enemy.get_level() > 2));
+
+    // The budget overflows long before this
+    let endless = enemies(&generator, 100_000, 42);
+    assert_eq!(endless.len(), MAX_ENDLESS_ENEMIES);
+    assert!(endless
+        .iter()
+        .all(|enemy| enemy.get_level() <= 2 + MAX_ENDLESS_EXTRA_LEVELS));
+}
+
+#[test]
+fn budget_keeps_growing_past_the_last_i32_round() {
+    let generator = generator();
+    let budget = generator.get_budget(i32::MAX as usize + 1);
+    assert!(budget >= generator.get_budget(1000));
+}
+
+#[test]
+fn leveled_up_enemies_drop_more() {
+    // Only the cheapest wasp fits, what is left of the budget levels it up
+    let wasps = |base_budget| EndlessWaveGenerator {
+        base_budget,
+        max_single_enemy_share: 0.0,
+        ..generator()
+    };
+    let base = wasps(40.0).generate(0, 42).enemies;
+    let leveled = wasps(400.0).generate(0, 42).enemies;
+    assert!(base.iter().all(|spawn| spawn.enemy == EnemyType::Wasp(0)));
+    assert!(leveled.iter().all(|spawn| spawn.enemy.get_level() > 0));
+
+    assert!(leveled
+        .iter()
+        .zip(base.iter())
+        .all(|(leveled, base)| leveled.drop[0] > base.drop[0]));
+}
+
+#[test]
+fn endless_delay_must_be_finite() {
+    let waves = br#"{
+        "endless": {
+            "delay": 1e39,
+            "base_budget": 10.0,
+            "budget_growth": 1.1,
+            "max_single_enemy_share": 0.5
+        },
+        "waves": [
+            { "delay": 1.0, "enemies": [] }
+        ]
+    }"#;
+
+    assert!(matches!(
+        WaveSet::from_slice(waves),
+        Err(WaveSetLoaderError::InvalidEndless(_))
+    ));
+}